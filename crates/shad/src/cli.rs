@@ -47,6 +47,9 @@ pub struct RunArgs {
     /// Print FPS in standard output.
     #[clap(long, short, action)]
     pub fps: bool,
+    /// List of item counts of runtime-sized array buffers (e.g. `particles=1000`).
+    #[arg(short, long, num_args(0..), value_parser = parse_buffer_length)]
+    pub length: Vec<(String, u32)>,
 }
 
 impl RunArgs {
@@ -173,7 +176,10 @@ impl ApplicationHandler for WindowRunner {
         event: WindowEvent,
     ) {
         if let Some(receiver) = &mut self.runner_receiver {
-            if let Ok(Some(runner)) = receiver.try_recv() {
+            if let Ok(Some(mut runner)) = receiver.try_recv() {
+                for (buffer, length) in &self.args.length {
+                    runner.resize(buffer, *length);
+                }
                 self.runner = Some(runner);
                 self.runner_receiver = None;
             }
@@ -240,3 +246,15 @@ impl WindowRunner {
         }
     }
 }
+
+fn parse_buffer_length(arg: &str) -> Result<(String, u32), String> {
+    let (buffer, length) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("invalid format `{arg}`, expected `<buffer>=<length>`"))?;
+    let length = length
+        .parse()
+        .ok()
+        .filter(|&length| length > 0)
+        .ok_or_else(|| format!("invalid length `{length}`, expected a positive integer"))?;
+    Ok((buffer.into(), length))
+}
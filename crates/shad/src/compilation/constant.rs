@@ -1,19 +1,24 @@
 use crate::compilation::index::NodeIndex;
+use crate::compilation::node::{GenericArgs, NodeRef, NodeSource};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone)]
+/// Maximum number of loop iterations run during the evaluation of a constant expression.
+pub(crate) const MAX_CONSTANT_LOOP_ITERATIONS: u32 = 10_000;
+
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct ConstantValue {
     pub(crate) transpiled_type_name: String,
     pub(crate) data: ConstantData,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) enum ConstantData {
     F32(f32),
     I32(i32),
     U32(u32),
     Bool(bool),
     StructFields(Vec<ConstantStructFieldData>),
+    Array(Vec<ConstantValue>),
 }
 
 impl ConstantData {
@@ -24,9 +29,18 @@ impl ConstantData {
             unreachable!("constant is not a struct")
         }
     }
+
+    pub(crate) fn is_finite(&self) -> bool {
+        match self {
+            Self::F32(value) => value.is_finite(),
+            Self::I32(_) | Self::U32(_) | Self::Bool(_) => true,
+            Self::StructFields(fields) => fields.iter().all(|field| field.value.data.is_finite()),
+            Self::Array(items) => items.iter().all(|item| item.data.is_finite()),
+        }
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub(crate) struct ConstantStructFieldData {
     pub(crate) name: String,
     pub(crate) value: ConstantValue,
@@ -36,23 +50,105 @@ pub(crate) struct ConstantStructFieldData {
 #[derive(Debug)]
 pub(crate) struct ConstantContext<'a> {
     pub(crate) index: &'a NodeIndex,
+    pub(crate) interruption: Option<ConstantInterruption>,
+    // The generic arguments of the evaluated function.
+    generic_args: GenericArgs<'a>,
+    generic_values: Vec<Option<ConstantValue>>,
     scopes: Vec<Scope>,
+    loop_iteration_count: u32,
+    is_generic_value_missing: bool,
 }
 
 impl<'a> ConstantContext<'a> {
     pub(crate) fn new(index: &'a NodeIndex) -> Self {
         Self {
             index,
+            interruption: None,
+            generic_args: vec![],
+            generic_values: vec![],
             scopes: vec![],
+            loop_iteration_count: 0,
+            is_generic_value_missing: false,
+        }
+    }
+
+    pub(crate) fn with_generic_args(index: &'a NodeIndex, generic_args: &GenericArgs<'_>) -> Self {
+        Self {
+            generic_values: generic_values(generic_args),
+            ..Self::new(index)
+        }
+    }
+
+    pub(crate) fn is_interrupted(&self) -> bool {
+        self.interruption.is_some()
+    }
+
+    pub(crate) fn is_loop_iteration_limit_reached(&self) -> bool {
+        self.interruption == Some(ConstantInterruption::LoopIterationLimit)
+    }
+
+    pub(crate) fn is_index_out_of_bounds(&self) -> bool {
+        self.interruption == Some(ConstantInterruption::IndexOutOfBounds)
+    }
+
+    pub(crate) fn is_non_finite_value(&self) -> bool {
+        self.interruption == Some(ConstantInterruption::NonFiniteValue)
+    }
+
+    pub(crate) fn is_division_by_zero(&self) -> bool {
+        self.interruption == Some(ConstantInterruption::DivisionByZero)
+    }
+
+    pub(crate) fn is_native_fn_implementation_missing(&self) -> bool {
+        self.interruption == Some(ConstantInterruption::MissingNativeFnImplementation)
+    }
+
+    // A missing generic value means that the value can only be evaluated once specialized.
+    pub(crate) fn is_generic_value_missing(&self) -> bool {
+        self.is_generic_value_missing
+    }
+
+    pub(crate) fn start_loop_iteration(&mut self) -> bool {
+        self.loop_iteration_count += 1;
+        if self.loop_iteration_count > MAX_CONSTANT_LOOP_ITERATIONS {
+            self.interruption = Some(ConstantInterruption::LoopIterationLimit);
+            false
+        } else {
+            true
         }
     }
 
-    pub(crate) fn start_fn(&mut self, params: HashMap<u32, ConstantValue>) {
-        self.scopes.push(Scope { vars: params });
+    /// Evaluates a call to a function specialized with [`ConstantContext::specialized`].
+    ///
+    /// The function body is evaluated in a new context, so that types depending on the function
+    /// generic parameters can be specialized.
+    pub(crate) fn evaluate_fn(
+        &mut self,
+        fn_: &NodeSource<'_>,
+        params: HashMap<u32, ConstantValue>,
+    ) -> Option<ConstantValue> {
+        let mut fn_ctx = ConstantContext {
+            index: self.index,
+            interruption: None,
+            generic_args: fn_.generic_args.clone(),
+            generic_values: generic_values(&fn_.generic_args),
+            scopes: vec![Scope { vars: params }],
+            loop_iteration_count: self.loop_iteration_count,
+            is_generic_value_missing: false,
+        };
+        let value = fn_.node().evaluate_constant(&mut fn_ctx);
+        self.interruption = fn_ctx.interruption;
+        self.loop_iteration_count = fn_ctx.loop_iteration_count;
+        self.is_generic_value_missing |= fn_ctx.is_generic_value_missing;
+        value
     }
 
-    pub(crate) fn end_fn(&mut self) {
-        self.scopes.pop();
+    /// Specializes a type or a function with the generic arguments of the evaluated function.
+    pub(crate) fn specialized<'b>(&self, source: &NodeSource<'b>) -> NodeSource<'b>
+    where
+        'a: 'b,
+    {
+        source.specialized(&self.generic_args, self.index)
     }
 
     pub(crate) fn create_var(&mut self, id: u32, value: ConstantValue) {
@@ -60,6 +156,23 @@ impl<'a> ConstantContext<'a> {
         self.scopes[scope_count - 1].vars.insert(id, value);
     }
 
+    pub(crate) fn update_var(&mut self, id: u32, value: ConstantValue) {
+        if let Some(var) = self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find_map(|scope| scope.vars.get_mut(&id))
+        {
+            *var = value;
+        }
+    }
+
+    pub(crate) fn generic_value(&mut self, position: usize) -> Option<&ConstantValue> {
+        let value = self.generic_values.get(position).and_then(Option::as_ref);
+        self.is_generic_value_missing |= value.is_none();
+        value
+    }
+
     pub(crate) fn var_value(&self, id: u32) -> Option<&ConstantValue> {
         self.scopes
             .iter()
@@ -68,6 +181,30 @@ impl<'a> ConstantContext<'a> {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ConstantInterruption {
+    Break,
+    Continue,
+    LoopIterationLimit,
+    IndexOutOfBounds,
+    NonFiniteValue,
+    DivisionByZero,
+    MissingNativeFnImplementation,
+}
+
+fn generic_values(generic_args: &GenericArgs<'_>) -> Vec<Option<ConstantValue>> {
+    generic_args
+        .iter()
+        .map(|arg| match arg.as_ref()?.node {
+            NodeRef::Value(value) => Some(value.to_constant()),
+            NodeRef::Type(_)
+            | NodeRef::Other(_)
+            | NodeRef::NoReturn
+            | NodeRef::DeferredValue(_) => None,
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 struct Scope {
     vars: HashMap<u32, ConstantValue>,
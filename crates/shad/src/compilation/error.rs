@@ -61,7 +61,7 @@ impl Error {
             .enumerate()
             .map(|(index, token)| {
                 if index == 0 {
-                    token.to_string()
+                    token.clone()
                 } else if index == error.expected_tokens.len() - 1 {
                     format!(" or {token}")
                 } else {
@@ -104,13 +104,17 @@ impl Error {
             .fold(true)
             .path(&path)
             .annotations(annotations)];
-        // coverage: off (unused for now)
-        for inner in &error.inner {
+        let inner_paths = error
+            .inner
+            .iter()
+            .map(|inner| inner.path.display().to_string())
+            .collect::<Vec<_>>();
+        for (inner, inner_path) in error.inner.iter().zip(&inner_paths) {
             if inner.path != error.path {
                 snippets.push(
-                    Snippet::source(&error.code)
+                    Snippet::source(&inner.code)
                         .fold(true)
-                        .path(&path)
+                        .path(inner_path)
                         .annotation(
                             Self::annotation_level(error.level, inner.level)
                                 .span(inner.span.clone())
@@ -119,7 +123,6 @@ impl Error {
                 );
             }
         }
-        // coverage: on
         renderer.render(&[
             Group::with_title(Level::ERROR.primary_title(&error.message)).elements(snippets),
         ])
@@ -165,7 +168,7 @@ pub struct ValidationError {
     /// The file path.
     pub path: PathBuf,
     /// Inner errors providing more details.
-    pub inner: Vec<ValidationError>,
+    pub inner: Vec<Self>,
 }
 
 impl ValidationError {
@@ -8,6 +8,7 @@ use std::rc::Rc;
 #[derive(Default, Debug)]
 pub(crate) struct NodeIndex {
     nodes: HashMap<PathBuf, HashMap<String, Vec<Rc<dyn Node>>>>,
+    nodes_by_id: HashMap<u32, Rc<dyn Node>>,
     lookup_paths: HashMap<PathBuf, Vec<PathBuf>>,
 }
 
@@ -31,11 +32,16 @@ impl NodeIndex {
             .entry(key)
             .or_default()
             .push(node.clone());
+        self.nodes_by_id.insert(node.id, node.clone());
+    }
+
+    pub(crate) fn node(&self, id: u32) -> Option<&dyn Node> {
+        self.nodes_by_id.get(&id).map(|node| &**node)
     }
 
     pub(crate) fn search(
         &self,
-        node: &impl Node,
+        node: &(impl Node + ?Sized),
         key: &str,
         source_criteria: &'static [NodeSourceSearchCriteria],
     ) -> Option<&dyn Node> {
@@ -48,7 +54,7 @@ impl NodeIndex {
     pub(crate) fn search_in_path(
         &self,
         path: &Path,
-        node: &impl Node,
+        node: &(impl Node + ?Sized),
         key: &str,
         source_criteria: &'static [NodeSourceSearchCriteria],
     ) -> Option<&dyn Node> {
@@ -69,12 +75,11 @@ impl NodeIndex {
     }
 
     fn is_source_in_scope(
-        node: &impl Node,
+        node: &(impl Node + ?Sized),
         source_criteria: &'static [NodeSourceSearchCriteria],
         source: &Rc<dyn Node>,
         current_path: &Path,
     ) -> bool {
-        let node_parent_id = node.parent_ids.last().copied().unwrap_or(0);
         let source_parent_id = source.parent_ids.last().copied().unwrap_or(0);
         let is_source_root = source.parent_ids.len() == 2;
         source_criteria.iter().any(|criteria| {
@@ -89,7 +94,7 @@ impl NodeIndex {
                     });
             (node.path == current_path || source.is_public())
                 && (criteria.can_be_after
-                    || source.id < node_parent_id
+                    || source.span.end <= node.span.start
                     || node.path != current_path)
                 && source.node_type_id() == (criteria.node_type)()
                 && (is_source_root
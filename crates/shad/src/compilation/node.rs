@@ -1,10 +1,11 @@
-use crate::compilation::constant::{ConstantContext, ConstantValue};
+use crate::compilation::constant::{ConstantContext, ConstantData, ConstantValue};
 use crate::compilation::index::NodeIndex;
 use crate::compilation::parsing;
 use crate::compilation::parsing::ParsingContext;
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
-use crate::language::items::type_::TypeItem;
+use crate::language::items::interface::InterfaceFnItem;
+use crate::language::items::type_::{GenericParam, TypeItem};
 use crate::ParsingError;
 use derive_where::derive_where;
 use itertools::Itertools;
@@ -53,15 +54,19 @@ impl<'a> NodeSource<'a> {
     pub(crate) fn node(&self) -> &'a dyn Node {
         match self.node {
             NodeRef::Type(node) => node,
-            NodeRef::Other(node) => node,
+            NodeRef::Other(node) | NodeRef::DeferredValue(node) => node,
             NodeRef::NoReturn => unreachable!("<no return> is not a node"),
+            NodeRef::Value(_) => unreachable!("generic values are not nodes"),
         }
     }
 
     pub(crate) fn type_item(&self) -> Option<&'a dyn TypeItem> {
         match self.node {
             NodeRef::Type(node) => Some(node),
-            NodeRef::Other(_) | NodeRef::NoReturn => None,
+            NodeRef::Other(_)
+            | NodeRef::NoReturn
+            | NodeRef::Value(_)
+            | NodeRef::DeferredValue(_) => None,
         }
     }
 
@@ -70,7 +75,10 @@ impl<'a> NodeSource<'a> {
     }
 
     pub(crate) fn key(&self) -> NodeSourceKey {
-        NodeSourceKey {
+        if let NodeRef::Value(value) = self.node {
+            return NodeSourceKey::Value(value);
+        }
+        NodeSourceKey::Node {
             node_id: self.node().id,
             generic_arg_keys: self
                 .generic_args
@@ -80,26 +88,12 @@ impl<'a> NodeSource<'a> {
         }
     }
 
-    pub(crate) fn name_or_no_return(&self) -> String {
+    pub(crate) fn name_or_no_return(&self, index: &NodeIndex) -> String {
         match self.node {
-            NodeRef::Type(type_) => {
-                let ident_name = type_.ident().slice.clone();
-                let generic_names = self
-                    .generic_args
-                    .iter()
-                    .map(|type_| {
-                        type_
-                            .as_ref()
-                            .map_or_else(|| UNKNOWN_TYPE.into(), NodeSource::name_or_no_return)
-                    })
-                    .join(", ");
-                if generic_names.is_empty() {
-                    ident_name
-                } else {
-                    format!("{ident_name}<{generic_names}>")
-                }
-            }
+            NodeRef::Type(type_) => type_.name(index, &self.generic_args),
             NodeRef::NoReturn => NO_RETURN_TYPE.into(),
+            NodeRef::Value(value) => value.name(),
+            NodeRef::DeferredValue(node) => node.slice.clone(),
             NodeRef::Other(_) => unreachable!("non-type nodes have no name"),
         }
     }
@@ -107,26 +101,85 @@ impl<'a> NodeSource<'a> {
     pub(crate) fn transpiled_type_name(&self, index: &NodeIndex) -> String {
         match self.node {
             NodeRef::Type(type_) => type_.transpiled_name(index, &self.generic_args),
-            NodeRef::Other(_) | NodeRef::NoReturn => unreachable!("not transpilable type"),
+            NodeRef::Value(value) => value.transpiled_name(),
+            NodeRef::Other(_) | NodeRef::NoReturn | NodeRef::DeferredValue(_) => {
+                unreachable!("not transpilable type")
+            }
+        }
+    }
+
+    pub(crate) fn is_generic(&self) -> bool {
+        self.type_item()
+            .is_some_and(|type_| (type_ as &dyn Any).is::<GenericParam>())
+            || matches!(self.node, NodeRef::DeferredValue(_))
+            || self.generic_args.iter().flatten().any(Self::is_generic)
+    }
+
+    pub(crate) fn specialized(&self, generic_args: &GenericArgs<'a>, index: &'a NodeIndex) -> Self {
+        if let NodeRef::DeferredValue(expr) = self.node {
+            let mut ctx = ConstantContext::with_generic_args(index, generic_args);
+            if let Some(value) = expr
+                .evaluate_constant(&mut ctx)
+                .and_then(|value| GenericValue::from_constant(&value.data))
+            {
+                return Self {
+                    node: NodeRef::Value(value),
+                    generic_args: vec![],
+                };
+            }
         }
+        if let Some(param) = self
+            .type_item()
+            .and_then(|type_| (type_ as &dyn Any).downcast_ref::<GenericParam>())
+        {
+            if let Some(Some(arg)) = param.position(index).and_then(|pos| generic_args.get(pos)) {
+                return arg.clone();
+            }
+        }
+        let specialized = Self {
+            node: self.node,
+            generic_args: self
+                .generic_args
+                .iter()
+                .map(|arg| arg.as_ref().map(|arg| arg.specialized(generic_args, index)))
+                .collect(),
+        };
+        // interface functions are replaced by their implementation once the type is known
+        if let NodeRef::Other(node) = specialized.node {
+            if let Some(fn_) = (node as &dyn Any).downcast_ref::<InterfaceFnItem>() {
+                if let Some(Some(type_)) = specialized.generic_args.first() {
+                    if !type_.is_generic() {
+                        if let Some(implementation) = fn_.implementation(type_, index) {
+                            return implementation;
+                        }
+                    }
+                }
+            }
+        }
+        specialized
     }
 
-    pub(crate) fn are_same_types(&self, other: &NodeSource<'_>) -> Option<bool> {
+    pub(crate) fn are_same_types(&self, other: &NodeSource<'_>, index: &NodeIndex) -> Option<bool> {
         match (self.node, other.node) {
             (NodeRef::Type(type1), NodeRef::Type(type2)) => {
                 // Comparison of generic count is already done outside this function
-                if type1.id != type2.id {
+                if !type1.is_same_item(type2, index)? {
                     return Some(false);
                 }
                 for (arg1, arg2) in self.generic_args.iter().zip(&other.generic_args) {
                     let arg1 = arg1.as_ref()?;
                     let arg2 = arg2.as_ref()?;
-                    if !arg1.are_same_types(arg2)? {
+                    if !arg1.are_same_types(arg2, index)? {
                         return Some(false);
                     }
                 }
                 Some(true)
             }
+            (NodeRef::Value(value1), NodeRef::Value(value2)) => Some(value1 == value2),
+            (NodeRef::DeferredValue(expr1), NodeRef::DeferredValue(expr2)) => {
+                (expr1.id == expr2.id).then_some(true)
+            }
+            (NodeRef::DeferredValue(_), _) | (_, NodeRef::DeferredValue(_)) => None,
             (NodeRef::NoReturn, NodeRef::NoReturn) | (NodeRef::Other(_), NodeRef::Other(_)) => {
                 unreachable!("only concrete type nodes can be compared")
             }
@@ -140,12 +193,65 @@ pub(crate) enum NodeRef<'a> {
     Type(&'a dyn TypeItem),
     Other(&'a dyn Node),
     NoReturn,
+    Value(GenericValue),
+    /// A constant expression depending on generic values, evaluated once specialized.
+    DeferredValue(&'a dyn Node),
+}
+
+/// A constant value passed as generic argument.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum GenericValue {
+    U32(u32),
+    I32(i32),
+    Bool(bool),
+}
+
+impl GenericValue {
+    pub(crate) fn from_constant(data: &ConstantData) -> Option<Self> {
+        match data {
+            ConstantData::U32(value) => Some(Self::U32(*value)),
+            ConstantData::I32(value) => Some(Self::I32(*value)),
+            ConstantData::Bool(value) => Some(Self::Bool(*value)),
+            ConstantData::F32(_) | ConstantData::StructFields(_) | ConstantData::Array(_) => None,
+        }
+    }
+
+    pub(crate) fn to_constant(self) -> ConstantValue {
+        let (transpiled_type_name, data) = match self {
+            Self::U32(value) => ("u32", ConstantData::U32(value)),
+            Self::I32(value) => ("i32", ConstantData::I32(value)),
+            Self::Bool(value) => ("bool", ConstantData::Bool(value)),
+        };
+        ConstantValue {
+            transpiled_type_name: transpiled_type_name.into(),
+            data,
+        }
+    }
+
+    fn name(self) -> String {
+        match self {
+            Self::U32(value) => format!("{value}u"),
+            Self::I32(value) => format!("{value}"),
+            Self::Bool(value) => format!("{value}"),
+        }
+    }
+
+    fn transpiled_name(self) -> String {
+        match self {
+            Self::U32(value) => format!("{value}u"),
+            Self::I32(value) => format!("{value}i"),
+            Self::Bool(value) => format!("{}u", u32::from(value)),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub(crate) struct NodeSourceKey {
-    pub(crate) node_id: u32,
-    pub(crate) generic_arg_keys: Vec<Option<NodeSourceKey>>,
+pub(crate) enum NodeSourceKey {
+    Node {
+        node_id: u32,
+        generic_arg_keys: Vec<Option<Self>>,
+    },
+    Value(GenericValue),
 }
 
 // coverage: off (most default implementations are unreachable)
@@ -155,6 +261,11 @@ pub(crate) trait NodeConfig {
         None
     }
 
+    // A node defining several items is registered once per key.
+    fn keys(&self) -> Vec<String> {
+        self.key().into_iter().collect()
+    }
+
     fn is_public(&self) -> bool {
         true
     }
@@ -167,6 +278,10 @@ pub(crate) trait NodeConfig {
         None
     }
 
+    fn generic_params(&self) -> Vec<&GenericParam> {
+        vec![]
+    }
+
     fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
         unreachable!("`{}` node has no ref checking", type_name::<Self>())
     }
@@ -225,26 +340,57 @@ pub(crate) trait Node: Any + NodeConfig + Debug + Deref<Target = NodeProps> {
         let mut sources = vec![];
         let mut registered_source_ids = HashSet::new();
         let mut sources_to_process: HashMap<_, _> = iter::once((
-            NodeSourceKey {
+            NodeSourceKey::Node {
                 node_id: self.id,
                 generic_arg_keys: vec![],
             },
-            NodeSource {
-                node: NodeRef::Other(self as &dyn Node),
+            NestedSource {
+                source: NodeSource {
+                    node: NodeRef::Other(self as &dyn Node),
+                    generic_args: vec![],
+                },
                 generic_args: vec![],
+                parent_ids: vec![],
             },
         ))
         .collect();
         while !sources_to_process.is_empty() {
-            for source in mem::take(&mut sources_to_process).into_values() {
-                let node_key = source.key();
+            for nested in mem::take(&mut sources_to_process).into_values() {
+                let node_key = nested.source.key();
                 if registered_source_ids.contains(&node_key) {
                     continue;
                 }
                 registered_source_ids.insert(node_key);
-                for source_child in source.node().direct_nested_sources(index) {
+                let node = nested.source.node();
+                // generic parameters of nested nodes are resolved using the closest generic item
+                let generic_args = if node.generic_params().is_empty() {
+                    nested.generic_args
+                } else {
+                    nested.source.generic_args.clone()
+                };
+                let mut parent_ids = nested.parent_ids;
+                parent_ids.push(node.id);
+                for source_child in node.direct_nested_sources(index) {
+                    let source_child = source_child.specialized(&generic_args, index);
+                    // generic values are inlined, so they have no nested source
+                    if matches!(
+                        source_child.node,
+                        NodeRef::Value(_) | NodeRef::DeferredValue(_)
+                    ) {
+                        continue;
+                    }
                     sources.push(source_child.clone());
-                    sources_to_process.insert(source_child.key(), source_child);
+                    // recursive items are not processed again to avoid infinite generic expansion
+                    if !parent_ids.contains(&source_child.node().id) {
+                        sources_to_process.insert(
+                            source_child.key(),
+                            NestedSource {
+                                source: source_child,
+                                generic_args: generic_args.clone(),
+                                parent_ids: parent_ids.clone(),
+                            },
+                        );
+                    }
                 }
             }
         }
@@ -256,6 +402,12 @@ pub(crate) trait Node: Any + NodeConfig + Debug + Deref<Target = NodeProps> {
     }
 }
 
+struct NestedSource<'a> {
+    source: NodeSource<'a>,
+    generic_args: GenericArgs<'a>,
+    parent_ids: Vec<u32>,
+}
+
 #[derive(Debug)]
 pub(crate) struct Repeated<T, const MIN: usize, const MAX: usize> {
     nodes: Vec<Rc<T>>,
@@ -322,7 +474,7 @@ impl<T: Node, const MIN: usize, const MAX: usize> Node for Repeated<T, MIN, MAX>
 
     fn index(&self, index: &mut NodeIndex) {
         for node in &self.nodes {
-            debug_assert!(node.key().is_none());
+            debug_assert!(node.keys().is_empty());
             node.index(index);
         }
     }
@@ -585,7 +737,7 @@ macro_rules! sequence {
 
             fn index(&self, index: &mut crate::compilation::index::NodeIndex) {
                 $(
-                    if let Some(key) = self.$child.key() {
+                    for key in self.$child.keys() {
                         index.register(key, &self.$child);
                     }
                     self.$child.index(index);
@@ -678,7 +830,7 @@ macro_rules! choice {
             fn index(&self, index: &mut crate::compilation::index::NodeIndex) {
                 match self {
                     $(Self::$child(child) => {
-                        if let Some(key) = child.key() {
+                        for key in child.keys() {
                             index.register(key, child);
                         }
                         child.index(index);
@@ -864,11 +1016,11 @@ macro_rules! transform {
             fn index(&self, index: &mut crate::compilation::index::NodeIndex) {
                 match self {
                     Self::Parsed(child) => {
-                        debug_assert!(child.key().is_none());
+                        debug_assert!(child.keys().is_empty());
                         child.index(index);
                     }
                     Self::Transformed(child) => {
-                        debug_assert!(child.key().is_none());
+                        debug_assert!(child.keys().is_empty());
                         child.index(index);
                     }
                 }
@@ -78,6 +78,7 @@ pub(crate) fn parse_keyword(
 ) -> Result<NodeProps, ParsingError> {
     if ctx.code[ctx.offset..].starts_with(keyword)
         && is_next_char_valid(ctx.code, ctx.offset + keyword.len())
+        && !is_path_separator_start(ctx.code, ctx.offset, keyword)
     {
         let span = ctx.offset..ctx.offset + keyword.len();
         ctx.offset += keyword.len();
@@ -167,12 +168,33 @@ pub(crate) fn parse_repeated<T: Node>(
     ))
 }
 
+// Finds the first `{` that is not nested in parentheses, which is where a statement block starts.
+// Comments are already replaced by spaces in the parsed code (see `remove_comments`), so a `{`
+// in a comment is never considered.
+pub(crate) fn block_start_offset(ctx: &ParsingContext<'_>) -> usize {
+    let mut depth = 0_usize;
+    for (offset, char) in ctx.code[ctx.offset..].char_indices() {
+        match char {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            '{' if depth == 0 => return ctx.offset + offset,
+            _ => (),
+        }
+    }
+    ctx.code.len()
+}
+
 pub(crate) fn is_next_char_valid(code: &str, offset: usize) -> bool {
     let previous_char = code[..offset].chars().last().unwrap_or(' ');
     let next_char = code[offset..].chars().next().unwrap_or(' ');
     !(is_ident_char(previous_char) && is_ident_char(next_char))
 }
 
+// A single `:` must not match the start of a `::` separator, like in `f(State::Idle)`.
+fn is_path_separator_start(code: &str, offset: usize, keyword: &str) -> bool {
+    keyword == ":" && code[offset..].starts_with("::")
+}
+
 fn is_ident_char(char: char) -> bool {
     char.is_ascii_alphanumeric() || char == '_'
 }
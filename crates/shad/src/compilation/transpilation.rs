@@ -1,8 +1,10 @@
 use crate::compilation::index::NodeIndex;
-use crate::compilation::node::Node;
+use crate::compilation::node::{Node, NodeConfig};
 use crate::language::items::buffer::BufferItem;
-use crate::language::items::compute::{InitItem, RunItem};
+use crate::language::items::compute;
+use crate::language::items::compute::{Dispatch, InitItem, RunItem};
 use crate::language::items::Root;
+use crate::language::type_ref;
 use itertools::Itertools;
 use petgraph::graphmap::DiGraphMap;
 use std::any::Any;
@@ -14,10 +16,13 @@ use std::path::{Path, PathBuf};
 #[derive(Debug)]
 pub struct Program {
     /// The program GPU buffers.
+    ///
+    /// It includes the buffers of the prelude, like `prelude.frame_seed` which is updated by
+    /// the [`Runner`](crate::Runner) before each step.
     pub buffers: HashMap<String, Buffer>,
     /// The program `init` shaders, run only once at module creation.
     pub init_shaders: Vec<Shader>,
-    /// The program `run` shaders, run at each frame.
+    /// The program `run` shaders, run at each frame by default.
     pub run_shaders: Vec<Shader>,
 }
 
@@ -34,9 +39,11 @@ impl Program {
                 is_inlined: false,
                 is_returning_ref: false,
                 return_var_id: None,
+                returned_flag_id: None,
                 returned_ref: None,
             },
             generated_stmts: vec![],
+            break_flag_id: None,
             block_inline_mappings: vec![],
             root_path,
             next_binding: 0,
@@ -49,6 +56,7 @@ impl Program {
                 .collect(),
             init_shaders: Self::sorted_buffers(roots, index)
                 .into_iter()
+                .filter(|item| item.expr().is_some())
                 .map(|item| Shader::from_buffer_item(item, &mut ctx))
                 .collect::<Vec<_>>()
                 .into_iter()
@@ -102,17 +110,27 @@ impl Program {
 #[derive(Debug)]
 pub struct Buffer {
     /// The buffer size in bytes.
+    ///
+    /// For a runtime-sized array, this is the size of the array with a single item, as WGSL
+    /// doesn't support empty runtime-sized arrays. The array length is then `1` until the buffer
+    /// is resized.
     pub size_bytes: u32,
+    /// The item size in bytes if the buffer is a runtime-sized array.
+    pub item_size_bytes: Option<u32>,
     /// The buffer type name in Shad.
     pub type_name: String,
 }
 
 impl Buffer {
     fn new(item: &BufferItem, index: &NodeIndex) -> Self {
-        let type_ = item.buffer_type(index);
+        let type_ = item
+            .type_(index)
+            .expect("internal error: buffer type not found");
+        let size_bytes = item.buffer_type(index).size(index, &type_.generic_args);
         Self {
-            size_bytes: type_.size(index),
-            type_name: type_.ident().slice.clone(),
+            size_bytes,
+            item_size_bytes: type_ref::is_runtime_array(&type_).then_some(size_bytes),
+            type_name: type_.name_or_no_return(index),
         }
     }
 }
@@ -124,6 +142,16 @@ pub struct Shader {
     pub code: String,
     /// The buffers used by the shader.
     pub buffers: Vec<String>,
+    /// The number of invocations in a workgroup.
+    pub workgroup_size: u32,
+    /// The number of times the shader is invoked.
+    pub invocation_count: InvocationCount,
+    /// The number of frames between two runs of the shader.
+    ///
+    /// This is always `1` for `init` shaders.
+    pub frequency: u32,
+    /// The shader evaluating whether the shader is run, if any.
+    pub condition: Option<ConditionShader>,
 }
 
 impl Shader {
@@ -135,25 +163,56 @@ impl Shader {
                 .into_iter()
                 .chain([item.item_path(ctx.root_path)])
                 .collect(),
+            workgroup_size: 1,
+            invocation_count: InvocationCount::Fixed(1),
+            frequency: 1,
+            condition: None,
         }
     }
 
     fn from_init_item(item: &InitItem, ctx: &mut TranspilationContext<'_>) -> Self {
         ctx.next_binding = 0;
+        let (workgroup_size, invocation_count) = Self::dispatch(item.dispatch(), ctx);
         Self {
             code: item.transpile_shader(ctx),
             buffers: Self::find_buffers(item, ctx),
+            workgroup_size,
+            invocation_count,
+            frequency: 1,
+            condition: None,
         }
     }
 
     fn from_run_item(item: &RunItem, ctx: &mut TranspilationContext<'_>) -> Self {
         ctx.next_binding = 0;
+        let (workgroup_size, invocation_count) = Self::dispatch(item.dispatch(), ctx);
+        let code = item.transpile_shader(ctx);
+        let condition = item.condition().map(|condition| {
+            ctx.next_binding = 0;
+            ConditionShader {
+                code: condition.transpile_shader(ctx),
+                buffers: Self::find_buffers(condition, ctx),
+            }
+        });
         Self {
-            code: item.transpile_shader(ctx),
+            code,
             buffers: Self::find_buffers(item, ctx),
+            workgroup_size,
+            invocation_count,
+            frequency: item.frequency(ctx.index),
+            condition,
         }
     }
 
+    fn dispatch(
+        dispatch: Option<&Dispatch>,
+        ctx: &TranspilationContext<'_>,
+    ) -> (u32, InvocationCount) {
+        dispatch.map_or((1, InvocationCount::Fixed(1)), |dispatch| {
+            (compute::WORKGROUP_SIZE, dispatch.invocation_count(ctx))
+        })
+    }
+
     fn find_buffers(item: &impl Node, ctx: &TranspilationContext<'_>) -> Vec<String> {
         item.nested_sources(ctx.index)
             .iter()
@@ -163,13 +222,41 @@ impl Shader {
     }
 }
 
+/// A shader evaluating whether a `run` shader is run in the current frame.
+///
+/// The shader writes `0` or `1` in the last item of the indirect dispatch arguments of the
+/// `run` shader, which are bound just after the shader buffers.
+#[derive(Debug)]
+pub struct ConditionShader {
+    /// The shader WGSL code.
+    pub code: String,
+    /// The buffers used by the shader.
+    pub buffers: Vec<String>,
+}
+
+/// The number of invocations of a shader.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InvocationCount {
+    /// A number of invocations known at compile time.
+    Fixed(u32),
+    /// As many invocations as items in a buffer containing a runtime-sized array.
+    ///
+    /// The buffer name is stored in the variant.
+    BufferLength(String),
+    /// As many invocations as the value of a `u32` buffer, read when the shader is run.
+    ///
+    /// The buffer name is stored in the variant.
+    Indirect(String),
+}
+
 #[derive(Debug)]
 pub(crate) struct TranspilationContext<'a> {
     pub(crate) index: &'a NodeIndex,
     pub(crate) generated_stmts: Vec<String>,
     pub(crate) inline_state: InlineState,
+    pub(crate) break_flag_id: Option<u32>,
     block_inline_mappings: Vec<HashMap<u32, String>>,
-    root_path: &'a Path,
+    pub(crate) root_path: &'a Path,
     next_binding: u32,
     next_node_id: u32,
 }
@@ -214,5 +301,6 @@ pub(crate) struct InlineState {
     pub(crate) is_inlined: bool,
     pub(crate) is_returning_ref: bool,
     pub(crate) return_var_id: Option<u32>,
+    pub(crate) returned_flag_id: Option<u32>,
     pub(crate) returned_ref: Option<String>,
 }
@@ -17,7 +17,27 @@ pub(crate) fn run(
     index: &NodeIndex,
     root_path: &Path,
 ) -> Result<(), Error> {
-    let errors = roots
+    let mut errors = validate_roots(roots, index, root_path, |root, ctx| {
+        root.validate_nested(ctx);
+    });
+    if errors.is_empty() {
+        // constants are evaluated only once the code is valid, as the evaluation relies on it
+        errors = validate_roots(roots, index, root_path, Root::validate_constant_evaluations);
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Validation(errors))
+    }
+}
+
+fn validate_roots(
+    roots: &HashMap<PathBuf, Root>,
+    index: &NodeIndex,
+    root_path: &Path,
+    validate: impl Fn(&Root, &mut ValidationContext<'_>),
+) -> Vec<ValidationError> {
+    roots
         .values()
         .flat_map(|root| {
             let mut ctx = ValidationContext {
@@ -26,13 +46,8 @@ pub(crate) fn run(
                 root_path,
                 errors: vec![],
             };
-            root.validate_nested(&mut ctx);
+            validate(root, &mut ctx);
             ctx.errors
         })
-        .collect::<Vec<_>>();
-    if errors.is_empty() {
-        Ok(())
-    } else {
-        Err(Error::Validation(errors))
-    }
+        .collect()
 }
@@ -1,8 +1,11 @@
 use crate::exec::utils;
-use crate::{Program, Shader};
+use crate::{InvocationCount, Program, Shader};
+use indoc::indoc;
 use std::collections::HashMap;
+use std::slice;
 use wgpu::{
-    BindGroup, BindGroupLayout, Buffer, ComputePass, ComputePipeline, Device, ShaderStages,
+    BindGroup, BindGroupEntry, BindGroupLayout, BindingResource, Buffer, BufferBinding, BufferSize,
+    ComputePass, ComputePipeline, Device, Queue, ShaderStages,
 };
 
 #[derive(Debug)]
@@ -10,10 +13,12 @@ pub(crate) struct ProgramResources {
     pub(crate) program: Program,
     pub(crate) buffers: HashMap<String, Buffer>,
     compute_shaders: Vec<ComputeShader>,
+    max_workgroup_count: u32,
 }
 
 impl ProgramResources {
-    pub(crate) fn new(device: &Device, program: Program) -> Self {
+    pub(crate) fn new(device: &Device, queue: &Queue, program: Program) -> Self {
+        let max_workgroup_count = device.limits().max_compute_workgroups_per_dimension;
         let buffers = program
             .buffers
             .iter()
@@ -24,42 +29,167 @@ impl ProgramResources {
                 )
             })
             .collect();
-        let init_shaders = program
-            .init_shaders
-            .iter()
-            .filter_map(|shader| ComputeShader::new(device, &buffers, shader, true));
-        let run_shaders = program
-            .run_shaders
-            .iter()
-            .filter_map(|shader| ComputeShader::new(device, &buffers, shader, false));
+        let init_shaders = program.init_shaders.iter().filter_map(|shader| {
+            ComputeShader::new(device, &buffers, shader, max_workgroup_count, true)
+        });
+        let run_shaders = program.run_shaders.iter().filter_map(|shader| {
+            ComputeShader::new(device, &buffers, shader, max_workgroup_count, false)
+        });
         let compute_shaders = init_shaders.chain(run_shaders).collect();
-        Self {
+        let resources = Self {
             program,
             buffers,
             compute_shaders,
+            max_workgroup_count,
+        };
+        resources.write_direct_workgroup_counts(queue);
+        resources
+    }
+
+    pub(crate) fn resize_buffer(&mut self, device: &Device, queue: &Queue, name: &str, size: u64) {
+        if let Some(buffer) = self.buffers.get_mut(name) {
+            let resized_buffer = utils::create_buffer(device, name, size);
+            let mut encoder = utils::create_encoder(device);
+            let copied_size = buffer.size().min(size);
+            encoder.copy_buffer_to_buffer(buffer, 0, &resized_buffer, 0, Some(copied_size));
+            queue.submit(Some(encoder.finish()));
+            *buffer = resized_buffer;
+            for shader in &mut self.compute_shaders {
+                if shader.buffers.iter().any(|buffer_name| buffer_name == name) {
+                    shader.bind_group = ComputeShader::create_bind_group(
+                        device,
+                        &shader.layout,
+                        &shader.buffers,
+                        &self.buffers,
+                    );
+                }
+                if let Some(indirect) = &mut shader.indirect {
+                    for args_pass in &mut indirect.passes {
+                        if args_pass
+                            .buffers
+                            .iter()
+                            .any(|buffer_name| buffer_name == name)
+                        {
+                            args_pass.bind_group = ArgsPass::create_bind_group(
+                                device,
+                                &args_pass.layout,
+                                &args_pass.buffers,
+                                &self.buffers,
+                                &indirect.buffer,
+                            );
+                        }
+                    }
+                }
+            }
+            self.write_direct_workgroup_counts(queue);
         }
     }
 
-    pub(crate) fn has_compute_step(&self) -> bool {
-        self.compute_shaders.iter().any(ComputeShader::should_run)
+    pub(crate) fn has_compute_step(&self, frame_index: u32) -> bool {
+        self.compute_shaders
+            .iter()
+            .any(|shader| shader.should_run(frame_index))
     }
 
-    pub(crate) fn run_compute_step(&mut self, mut pass: ComputePass<'_>) {
+    pub(crate) fn run_compute_step(&mut self, mut pass: ComputePass<'_>, frame_index: u32) {
         for shader in &mut self.compute_shaders {
-            if shader.should_run() {
+            if !shader.should_run(frame_index) {
+                continue;
+            }
+            if let Some(indirect) = &shader.indirect {
+                for args_pass in &indirect.passes {
+                    pass.set_pipeline(&args_pass.pipeline);
+                    pass.set_bind_group(0, &args_pass.bind_group, &[]);
+                    pass.dispatch_workgroups(1, 1, 1);
+                }
+                pass.set_pipeline(&shader.pipeline);
+                pass.set_bind_group(0, &shader.bind_group, &[]);
+                pass.dispatch_workgroups_indirect(&indirect.buffer, 0);
+            } else {
+                let invocation_count =
+                    direct_invocation_count(&self.program, &self.buffers, &shader.invocation_count)
+                        .expect("internal error: indirect shader without arguments");
+                let (x, y) = workgroup_count(
+                    invocation_count,
+                    shader.workgroup_size,
+                    self.max_workgroup_count,
+                );
                 pass.set_pipeline(&shader.pipeline);
                 pass.set_bind_group(0, &shader.bind_group, &[]);
-                pass.dispatch_workgroups(1, 1, 1);
-                shader.is_init_done = true;
+                pass.dispatch_workgroups(x, y, 1);
             }
+            shader.is_init_done = true;
         }
     }
+
+    // Indirect dispatches with a direct invocation count only have their `z` workgroup count
+    // calculated on GPU side, by the condition shader.
+    fn write_direct_workgroup_counts(&self, queue: &Queue) {
+        for shader in &self.compute_shaders {
+            let Some(indirect) = &shader.indirect else {
+                continue;
+            };
+            if let Some(invocation_count) =
+                direct_invocation_count(&self.program, &self.buffers, &shader.invocation_count)
+            {
+                let (x, y) = workgroup_count(
+                    invocation_count,
+                    shader.workgroup_size,
+                    self.max_workgroup_count,
+                );
+                queue.write_buffer(
+                    &indirect.buffer,
+                    0,
+                    &[x.to_ne_bytes(), y.to_ne_bytes()].concat(),
+                );
+            }
+        }
+    }
+}
+
+fn direct_invocation_count(
+    program: &Program,
+    buffers: &HashMap<String, Buffer>,
+    invocation_count: &InvocationCount,
+) -> Option<u32> {
+    match invocation_count {
+        InvocationCount::Fixed(count) => Some(*count),
+        InvocationCount::BufferLength(name) => Some(buffer_length(program, buffers, name)),
+        InvocationCount::Indirect(_) => None,
+    }
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::integer_division)]
+fn buffer_length(program: &Program, buffers: &HashMap<String, Buffer>, name: &str) -> u32 {
+    let item_size = program.buffers[name]
+        .item_size_bytes
+        .expect("internal error: buffer is not a runtime-sized array");
+    (buffers[name].size() / u64::from(item_size)) as u32
+}
+
+// Workgroups are spread over the `y` dimension when there are too many for the `x` dimension.
+fn workgroup_count(invocation_count: u32, workgroup_size: u32, max_count: u32) -> (u32, u32) {
+    let count = invocation_count.div_ceil(workgroup_size);
+    if count <= max_count {
+        (count, 1)
+    } else {
+        (max_count, count.div_ceil(max_count))
+    }
 }
 
+// The size of the `x`, `y` and `z` workgroup counts of an indirect dispatch.
+const INDIRECT_ARGS_SIZE: u64 = 12;
+
 #[derive(Debug)]
 struct ComputeShader {
     pub(crate) pipeline: ComputePipeline,
+    pub(crate) layout: BindGroupLayout,
     pub(crate) bind_group: BindGroup,
+    pub(crate) buffers: Vec<String>,
+    pub(crate) workgroup_size: u32,
+    pub(crate) invocation_count: InvocationCount,
+    pub(crate) indirect: Option<IndirectArgs>,
+    pub(crate) frequency: u32,
     pub(crate) is_init: bool,
     pub(crate) is_init_done: bool,
 }
@@ -70,6 +200,7 @@ impl ComputeShader {
         device: &Device,
         buffers: &HashMap<String, Buffer>,
         shader: &Shader,
+        max_workgroup_count: u32,
         is_init: bool,
     ) -> Option<Self> {
         let layout = utils::create_bind_group_layout(
@@ -78,10 +209,16 @@ impl ComputeShader {
             shader.buffers.len() as u32,
         )?;
         let pipeline = utils::create_compute_pipeline(device, &layout, &shader.code);
-        let bind_group = Self::create_bind_group(device, &layout, shader, buffers);
+        let bind_group = Self::create_bind_group(device, &layout, &shader.buffers, buffers);
         Some(Self {
             pipeline,
+            layout,
             bind_group,
+            buffers: shader.buffers.clone(),
+            workgroup_size: shader.workgroup_size,
+            invocation_count: shader.invocation_count.clone(),
+            indirect: IndirectArgs::new(device, buffers, shader, max_workgroup_count),
+            frequency: shader.frequency,
             is_init,
             is_init_done: false,
         })
@@ -91,25 +228,166 @@ impl ComputeShader {
     fn create_bind_group(
         device: &Device,
         layout: &BindGroupLayout,
-        shader: &Shader,
+        buffer_names: &[String],
         buffers: &HashMap<String, Buffer>,
     ) -> BindGroup {
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("shad:bind_group"),
             layout,
-            entries: &shader
-                .buffers
-                .iter()
-                .enumerate()
-                .map(|(binding, name)| wgpu::BindGroupEntry {
-                    binding: binding as u32,
-                    resource: buffers[name].as_entire_binding(),
-                })
-                .collect::<Vec<_>>(),
+            entries: &buffer_entries(buffer_names, buffers),
         })
     }
 
-    fn should_run(&self) -> bool {
-        !self.is_init || !self.is_init_done
+    fn should_run(&self, frame_index: u32) -> bool {
+        if self.is_init {
+            !self.is_init_done
+        } else {
+            frame_index.is_multiple_of(self.frequency)
+        }
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn buffer_entries<'a>(
+    buffer_names: &[String],
+    buffers: &'a HashMap<String, Buffer>,
+) -> Vec<BindGroupEntry<'a>> {
+    buffer_names
+        .iter()
+        .enumerate()
+        .map(|(binding, name)| BindGroupEntry {
+            binding: binding as u32,
+            // explicit size, as wgpu may allocate more than requested (which would
+            // impact the result of `arrayLength`)
+            resource: BindingResource::Buffer(BufferBinding {
+                buffer: &buffers[name],
+                offset: 0,
+                size: BufferSize::new(buffers[name].size()),
+            }),
+        })
+        .collect()
+}
+
+// The arguments of an indirect dispatch are calculated on GPU side by shaders run just before the
+// dispatch, so that no CPU round trip is needed:
+// - the workgroup counts are calculated from a buffer invocation count, the same way as
+//   `workgroup_count` does on CPU side
+// - the `z` workgroup count is set to 0 by the condition shader if the shader shouldn't run
+#[derive(Debug)]
+struct IndirectArgs {
+    buffer: Buffer,
+    passes: Vec<ArgsPass>,
+}
+
+impl IndirectArgs {
+    fn new(
+        device: &Device,
+        buffers: &HashMap<String, Buffer>,
+        shader: &Shader,
+        max_workgroup_count: u32,
+    ) -> Option<Self> {
+        let count_pass_source = match &shader.invocation_count {
+            InvocationCount::Indirect(count_buffer) => Some((
+                Self::count_code(shader.workgroup_size, max_workgroup_count),
+                slice::from_ref(count_buffer),
+            )),
+            InvocationCount::Fixed(_) | InvocationCount::BufferLength(_) => None,
+        };
+        let condition_pass_source = shader
+            .condition
+            .as_ref()
+            .map(|condition| (condition.code.clone(), condition.buffers.as_slice()));
+        let pass_sources: Vec<_> = count_pass_source
+            .into_iter()
+            .chain(condition_pass_source)
+            .collect();
+        if pass_sources.is_empty() {
+            return None;
+        }
+        let buffer = utils::create_buffer(device, "shad:indirect_args", INDIRECT_ARGS_SIZE);
+        let passes = pass_sources
+            .iter()
+            .map(|(code, buffer_names)| ArgsPass::new(device, buffers, code, buffer_names, &buffer))
+            .collect();
+        Some(Self { buffer, passes })
+    }
+
+    fn count_code(workgroup_size: u32, max_workgroup_count: u32) -> String {
+        format!(
+            indoc!(
+                "
+                @group(0) @binding(0) var<storage, read_write> count: u32;
+                @group(0) @binding(1) var<storage, read_write> args: array<u32, 3>;
+
+                @compute
+                @workgroup_size(1, 1, 1)
+                fn main() {{
+                    let workgroup_count = count / {workgroup_size}u
+                        + select(0u, 1u, count % {workgroup_size}u != 0u);
+                    args[0] = min(workgroup_count, {max_workgroup_count}u);
+                    args[1] = workgroup_count / {max_workgroup_count}u
+                        + select(0u, 1u, workgroup_count % {max_workgroup_count}u != 0u);
+                    args[2] = 1u;
+                }}"
+            ),
+            workgroup_size = workgroup_size,
+            max_workgroup_count = max_workgroup_count,
+        )
+    }
+}
+
+// A shader writing indirect dispatch arguments, which are bound just after the shader buffers.
+#[derive(Debug)]
+struct ArgsPass {
+    pipeline: ComputePipeline,
+    layout: BindGroupLayout,
+    bind_group: BindGroup,
+    buffers: Vec<String>,
+}
+
+impl ArgsPass {
+    #[allow(clippy::cast_possible_truncation)]
+    fn new(
+        device: &Device,
+        buffers: &HashMap<String, Buffer>,
+        code: &str,
+        buffer_names: &[String],
+        args_buffer: &Buffer,
+    ) -> Self {
+        let layout = utils::create_bind_group_layout(
+            device,
+            ShaderStages::COMPUTE,
+            buffer_names.len() as u32 + 1,
+        )
+        .expect("internal error: missing indirect bind group layout");
+        let pipeline = utils::create_compute_pipeline(device, &layout, code);
+        let bind_group =
+            Self::create_bind_group(device, &layout, buffer_names, buffers, args_buffer);
+        Self {
+            pipeline,
+            layout,
+            bind_group,
+            buffers: buffer_names.to_vec(),
+        }
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    fn create_bind_group(
+        device: &Device,
+        layout: &BindGroupLayout,
+        buffer_names: &[String],
+        buffers: &HashMap<String, Buffer>,
+        args_buffer: &Buffer,
+    ) -> BindGroup {
+        let mut entries = buffer_entries(buffer_names, buffers);
+        entries.push(BindGroupEntry {
+            binding: buffer_names.len() as u32,
+            resource: args_buffer.as_entire_binding(),
+        });
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("shad:indirect_bind_group"),
+            layout,
+            entries: &entries,
+        })
     }
 }
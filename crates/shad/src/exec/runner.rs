@@ -9,6 +9,8 @@ use wgpu::{Adapter, Device, Instance, Queue, TextureViewDescriptor};
 use winit::dpi::PhysicalSize;
 use winit::window::Window;
 
+const FRAME_SEED_BUFFER: &str = "prelude.frame_seed";
+
 /// A runner to execute a Shad program.
 #[derive(Debug)]
 pub struct Runner {
@@ -20,6 +22,7 @@ pub struct Runner {
     resources: ProgramResources,
     frame_delta_secs: f32,
     last_frame_end: Instant,
+    frame_index: u32,
 }
 
 impl Runner {
@@ -80,7 +83,7 @@ impl Runner {
                 depth_buffer,
             }
         };
-        let resources = ProgramResources::new(&device, program);
+        let resources = ProgramResources::new(&device, &queue, program);
         Self {
             target,
             device,
@@ -90,6 +93,7 @@ impl Runner {
             instance,
             frame_delta_secs: 0.,
             last_frame_end: Instant::now(),
+            frame_index: 0,
         }
     }
 
@@ -113,16 +117,45 @@ impl Runner {
     /// This will panic if the `data` length doesn't match the buffer size.
     #[allow(clippy::cast_possible_truncation)]
     pub fn write(&self, buffer_name: &str, data: &[u8]) {
-        if let (Some(buffer_props), Some(buffer)) = (
-            self.resources.program.buffers.get(buffer_name),
-            self.resources.buffers.get(buffer_name),
-        ) {
-            let expected_size = buffer_props.size_bytes as usize;
+        if let Some(buffer) = self.resources.buffers.get(buffer_name) {
+            let expected_size = buffer.size() as usize;
             assert_eq!(data.len(), expected_size, "incorrect data size");
             self.queue.write_buffer(buffer, 0, data);
         }
     }
 
+    /// Sets the item count of a GPU buffer containing a runtime-sized array.
+    ///
+    /// Buffer name includes the module path in which the module is defined
+    /// (e.g. `inner.module.my_buffer`).
+    ///
+    /// Existing items are kept, and new items are initialized with zeros.
+    ///
+    /// Runtime-sized arrays contain a single item initialized with zeros until they are resized.
+    ///
+    /// If the buffer doesn't exist or is not a runtime-sized array, nothing happens.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `item_count` is zero.
+    pub fn resize(&mut self, buffer_name: &str, item_count: u32) {
+        assert!(
+            item_count > 0,
+            "runtime-sized array should have at least one item"
+        );
+        if let Some(item_size) = self
+            .resources
+            .program
+            .buffers
+            .get(buffer_name)
+            .and_then(|buffer| buffer.item_size_bytes)
+        {
+            let size = u64::from(item_size) * u64::from(item_count);
+            self.resources
+                .resize_buffer(&self.device, &self.queue, buffer_name, size);
+        }
+    }
+
     /// Reads GPU buffer data.
     ///
     /// Buffer name includes the module path in which the module is defined
@@ -130,16 +163,8 @@ impl Runner {
     ///
     /// If the buffer doesn't exist, an empty vector is returned.
     pub fn read(&self, buffer_name: &str) -> Vec<u8> {
-        if let (Some(buffer_props), Some(buffer)) = (
-            self.resources.program.buffers.get(buffer_name),
-            self.resources.buffers.get(buffer_name),
-        ) {
-            utils::read_buffer(
-                &self.device,
-                &self.queue,
-                buffer,
-                buffer_props.size_bytes.into(),
-            )
+        if let Some(buffer) = self.resources.buffers.get(buffer_name) {
+            utils::read_buffer(&self.device, &self.queue, buffer, buffer.size())
         } else {
             vec![]
         }
@@ -163,10 +188,13 @@ impl Runner {
     ///
     /// An error is returned if shader execution failed.
     pub fn run_step(&mut self) {
+        let frame_index = self.frame_index;
+        self.write(FRAME_SEED_BUFFER, &frame_index.to_ne_bytes());
+        self.frame_index = frame_index.wrapping_add(1);
         let mut encoder = utils::create_encoder(&self.device);
-        if self.resources.has_compute_step() {
+        if self.resources.has_compute_step(frame_index) {
             let pass = utils::start_compute_pass(&mut encoder);
-            self.resources.run_compute_step(pass);
+            self.resources.run_compute_step(pass, frame_index);
         }
         match &self.target.inner {
             // coverage: off (window cannot be tested)
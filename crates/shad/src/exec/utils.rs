@@ -77,7 +77,8 @@ pub(crate) fn create_buffer(device: &Device, label: &str, size: u64) -> Buffer {
             | BufferUsages::COPY_DST
             | BufferUsages::UNIFORM
             | BufferUsages::VERTEX
-            | BufferUsages::INDEX,
+            | BufferUsages::INDEX
+            | BufferUsages::INDIRECT,
         mapped_at_creation: false,
     })
 }
@@ -4,6 +4,8 @@ use crate::compilation::constant::{
 use crate::compilation::node::Node;
 use crate::language::items::fn_;
 use std::collections::HashMap;
+use std::iter;
+use std::ops::{BitAnd, BitOr, BitXor};
 
 mod native_fns_bool;
 mod native_fns_f32;
@@ -23,20 +25,34 @@ pub(crate) fn evaluate_fn_args<'a>(
     fn_: &dyn Node,
     args: impl Iterator<Item = &'a impl Node>,
     ctx: &mut ConstantContext<'_>,
-) -> HashMap<u32, ConstantValue> {
+) -> Option<HashMap<u32, ConstantValue>> {
     fn_::signature(fn_)
         .params()
         .zip(args)
-        .map(|(param, arg)| {
-            (
-                param.id,
-                arg.evaluate_constant(ctx)
-                    .expect("internal error: invalid fn arg"),
-            )
-        })
+        .map(|(param, arg)| Some((param.id, arg.evaluate_constant(ctx)?)))
         .collect()
 }
 
+// Float division by zero is not checked, as it produces a non-finite value.
+pub(crate) fn is_division_by_zero(fn_name: &str, params: &[&ConstantValue]) -> bool {
+    matches!(fn_name, "__div__" | "__mod__")
+        && params
+            .get(1)
+            .is_some_and(|divisor| contains_integer_zero(&divisor.data))
+}
+
+fn contains_integer_zero(data: &ConstantData) -> bool {
+    match data {
+        ConstantData::I32(value) => *value == 0,
+        ConstantData::U32(value) => *value == 0,
+        ConstantData::F32(_) | ConstantData::Bool(_) => false,
+        ConstantData::StructFields(fields) => fields
+            .iter()
+            .any(|field| contains_integer_zero(&field.value.data)),
+        ConstantData::Array(items) => items.iter().any(|item| contains_integer_zero(&item.data)),
+    }
+}
+
 fn vec2(x: ConstantValue, y: ConstantValue) -> ConstantData {
     ConstantData::StructFields(vec![
         struct_field("x", x.clone()),
@@ -70,6 +86,35 @@ fn vec4(x: ConstantValue, y: ConstantValue, z: ConstantValue, w: ConstantValue)
     ])
 }
 
+pub(crate) fn swizzle(
+    value: &ConstantValue,
+    components: &str,
+    transpiled_type_name: String,
+) -> ConstantValue {
+    let fields = value.data.fields();
+    let items = components
+        .chars()
+        .map(|component| {
+            fields
+                .iter()
+                .find(|field| field.name.chars().eq(iter::once(component)))
+                .expect("internal error: swizzle component not found")
+                .value
+                .clone()
+        })
+        .collect::<Vec<_>>();
+    let data = match items.as_slice() {
+        [x, y] => vec2(x.clone(), y.clone()),
+        [x, y, z] => vec3(x.clone(), y.clone(), z.clone()),
+        [x, y, z, w] => vec4(x.clone(), y.clone(), z.clone(), w.clone()),
+        _ => unreachable!("invalid swizzle length"),
+    };
+    ConstantValue {
+        transpiled_type_name,
+        data,
+    }
+}
+
 fn struct_field(name: &str, value: ConstantValue) -> ConstantStructFieldData {
     ConstantStructFieldData {
         name: name.into(),
@@ -86,13 +131,39 @@ fn struct_field_alias(name: &str, value: ConstantValue) -> ConstantStructFieldDa
     }
 }
 
+// Floats never wrap, so the operations are the standard ones.
+trait WrappingArithmetic {
+    fn wrapping_add(self, other: Self) -> Self;
+    fn wrapping_sub(self, other: Self) -> Self;
+    fn wrapping_mul(self, other: Self) -> Self;
+    fn wrapping_div(self, other: Self) -> Self;
+}
+
+impl WrappingArithmetic for f32 {
+    fn wrapping_add(self, other: Self) -> Self {
+        self + other
+    }
+
+    fn wrapping_sub(self, other: Self) -> Self {
+        self - other
+    }
+
+    fn wrapping_mul(self, other: Self) -> Self {
+        self * other
+    }
+
+    fn wrapping_div(self, other: Self) -> Self {
+        self / other
+    }
+}
+
 macro_rules! const_numeric_binary_operator {
-    ($fn_name:ident, $operator:tt, [$($type_:ident),+]) => {
+    ($fn_name:ident, $method:ident, [$($type_:ident),+]) => {
         #[allow(clippy::integer_division)]
         fn $fn_name(left: &ConstantValue, right: &ConstantValue) -> ConstantData {
             match (&left.data, &right.data) {
                 $((ConstantData::$type_(left), ConstantData::$type_(right)) => {
-                    ConstantData::$type_(*left $operator *right)
+                    ConstantData::$type_(left.$method(*right))
                 })+
                 $((ConstantData::StructFields(left), ConstantData::$type_(_)) => {
                     ConstantData::StructFields(
@@ -171,6 +242,37 @@ macro_rules! const_bool_binary_operator {
     };
 }
 
+macro_rules! const_shift_operator {
+    ($fn_name:ident, $method:ident) => {
+        fn $fn_name(left: &ConstantValue, right: &ConstantValue) -> ConstantData {
+            match (&left.data, &right.data) {
+                (ConstantData::I32(left), ConstantData::U32(right)) => {
+                    ConstantData::I32(left.$method(*right))
+                }
+                (ConstantData::U32(left), ConstantData::U32(right)) => {
+                    ConstantData::U32(left.$method(*right))
+                }
+                (ConstantData::StructFields(left), ConstantData::StructFields(right)) => {
+                    ConstantData::StructFields(
+                        left.iter()
+                            .zip(right)
+                            .map(|(left, right)| ConstantStructFieldData {
+                                name: left.name.clone(),
+                                value: ConstantValue {
+                                    transpiled_type_name: left.value.transpiled_type_name.clone(),
+                                    data: $fn_name(&left.value, &right.value),
+                                },
+                                is_alias: left.is_alias,
+                            })
+                            .collect(),
+                    )
+                }
+                (_, _) => unreachable!("invalid const operands"),
+            }
+        }
+    };
+}
+
 macro_rules! const_unary_operator {
     ($fn_name:ident, $operator:tt, [$($type_:ident),+]) => {
         fn $fn_name(value: &ConstantValue) -> ConstantData {
@@ -196,11 +298,70 @@ macro_rules! const_unary_operator {
     };
 }
 
-const_numeric_binary_operator!(add, +, [F32, I32, U32]);
-const_numeric_binary_operator!(sub, -, [F32, I32, U32]);
-const_numeric_binary_operator!(mul, *, [F32, I32, U32]);
-const_numeric_binary_operator!(div, /, [F32, I32, U32]);
-const_numeric_binary_operator!(mod_, %, [I32, U32]);
+macro_rules! const_f32_unary_fn {
+    ($fn_name:ident, $method:ident) => {
+        fn $fn_name(value: &ConstantValue) -> ConstantData {
+            match &value.data {
+                ConstantData::F32(value) => ConstantData::F32(value.$method()),
+                ConstantData::StructFields(fields) => ConstantData::StructFields(
+                    fields
+                        .iter()
+                        .map(|field| ConstantStructFieldData {
+                            name: field.name.clone(),
+                            value: ConstantValue {
+                                transpiled_type_name: field.value.transpiled_type_name.clone(),
+                                data: $fn_name(&field.value),
+                            },
+                            is_alias: field.is_alias,
+                        })
+                        .collect(),
+                ),
+                _ => unreachable!("invalid const operands"),
+            }
+        }
+    };
+}
+
+macro_rules! const_f32_binary_fn {
+    ($fn_name:ident, $method:ident) => {
+        fn $fn_name(left: &ConstantValue, right: &ConstantValue) -> ConstantData {
+            match (&left.data, &right.data) {
+                (ConstantData::F32(left), ConstantData::F32(right)) => {
+                    ConstantData::F32(left.$method(*right))
+                }
+                (ConstantData::StructFields(left), ConstantData::StructFields(right)) => {
+                    ConstantData::StructFields(
+                        left.iter()
+                            .zip(right)
+                            .map(|(left, right)| ConstantStructFieldData {
+                                name: left.name.clone(),
+                                value: ConstantValue {
+                                    transpiled_type_name: left.value.transpiled_type_name.clone(),
+                                    data: $fn_name(&left.value, &right.value),
+                                },
+                                is_alias: left.is_alias,
+                            })
+                            .collect(),
+                    )
+                }
+                (_, _) => unreachable!("invalid const operands"),
+            }
+        }
+    };
+}
+
+// Like at runtime, integer operations wrap on overflow.
+const_numeric_binary_operator!(add, wrapping_add, [F32, I32, U32]);
+const_numeric_binary_operator!(sub, wrapping_sub, [F32, I32, U32]);
+const_numeric_binary_operator!(mul, wrapping_mul, [F32, I32, U32]);
+const_numeric_binary_operator!(div, wrapping_div, [F32, I32, U32]);
+const_numeric_binary_operator!(mod_, wrapping_rem, [I32, U32]);
+const_numeric_binary_operator!(band, bitand, [I32, U32]);
+const_numeric_binary_operator!(bor, bitor, [I32, U32]);
+const_numeric_binary_operator!(xor, bitxor, [I32, U32]);
+// Like at runtime, only the lowest bits of the shift amount are used.
+const_shift_operator!(shl, wrapping_shl);
+const_shift_operator!(shr, wrapping_shr);
 const_bool_binary_operator!(lt, <, [Bool, F32, I32, U32]);
 const_bool_binary_operator!(gt, >, [Bool, F32, I32, U32]);
 const_bool_binary_operator!(le, <=, [Bool, F32, I32, U32]);
@@ -211,3 +372,19 @@ const_bool_binary_operator!(and, &&, [Bool]);
 const_bool_binary_operator!(or, ||, [Bool]);
 const_unary_operator!(neg, -, [F32, I32]);
 const_unary_operator!(not, !, [Bool]);
+const_unary_operator!(bnot, !, [I32, U32]);
+const_f32_unary_fn!(acos, acos);
+const_f32_unary_fn!(acosh, acosh);
+const_f32_unary_fn!(asin, asin);
+const_f32_unary_fn!(asinh, asinh);
+const_f32_unary_fn!(atan, atan);
+const_f32_unary_fn!(atanh, atanh);
+const_f32_binary_fn!(atan2, atan2);
+const_f32_unary_fn!(cos, cos);
+const_f32_unary_fn!(cosh, cosh);
+const_f32_unary_fn!(sin, sin);
+const_f32_unary_fn!(sinh, sinh);
+const_f32_unary_fn!(tan, tan);
+const_f32_unary_fn!(tanh, tanh);
+const_f32_unary_fn!(degrees, to_degrees);
+const_f32_unary_fn!(radians, to_radians);
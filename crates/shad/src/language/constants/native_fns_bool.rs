@@ -135,7 +135,9 @@ fn to_bool(value: &ConstantValue) -> ConstantValue {
             ConstantData::F32(value) => ConstantData::Bool(value != 0.),
             ConstantData::I32(value) => ConstantData::Bool(value != 0),
             ConstantData::U32(value) => ConstantData::Bool(value != 0),
-            ConstantData::StructFields(_) => unreachable!("unsupported native conversion"),
+            ConstantData::StructFields(_) | ConstantData::Array(_) => {
+                unreachable!("unsupported native conversion")
+            }
         },
     }
 }
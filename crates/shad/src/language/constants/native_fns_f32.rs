@@ -2,7 +2,10 @@ use crate::compilation::constant::{ConstantData, ConstantValue};
 use crate::language::constants;
 
 pub(crate) fn runner(fn_key: &str) -> Option<fn(params: &[&ConstantValue]) -> ConstantData> {
-    constructor_runner(fn_key).or_else(|| operator_runner(fn_key))
+    constructor_runner(fn_key)
+        .or_else(|| operator_runner(fn_key))
+        .or_else(|| trigonometry_runner(fn_key))
+        .or_else(|| matrix_runner(fn_key))
 }
 
 fn constructor_runner(fn_key: &str) -> Option<fn(params: &[&ConstantValue]) -> ConstantData> {
@@ -133,6 +136,166 @@ fn operator_runner(fn_key: &str) -> Option<fn(params: &[&ConstantValue]) -> Cons
     })
 }
 
+fn trigonometry_runner(fn_key: &str) -> Option<fn(params: &[&ConstantValue]) -> ConstantData> {
+    Some(match fn_key {
+        "`acos(f32)` function"
+        | "`acos(f32x2)` function"
+        | "`acos(f32x3)` function"
+        | "`acos(f32x4)` function" => |p| constants::acos(p[0]),
+        "`acosh(f32)` function"
+        | "`acosh(f32x2)` function"
+        | "`acosh(f32x3)` function"
+        | "`acosh(f32x4)` function" => |p| constants::acosh(p[0]),
+        "`asin(f32)` function"
+        | "`asin(f32x2)` function"
+        | "`asin(f32x3)` function"
+        | "`asin(f32x4)` function" => |p| constants::asin(p[0]),
+        "`asinh(f32)` function"
+        | "`asinh(f32x2)` function"
+        | "`asinh(f32x3)` function"
+        | "`asinh(f32x4)` function" => |p| constants::asinh(p[0]),
+        "`atan(f32)` function"
+        | "`atan(f32x2)` function"
+        | "`atan(f32x3)` function"
+        | "`atan(f32x4)` function" => |p| constants::atan(p[0]),
+        "`atanh(f32)` function"
+        | "`atanh(f32x2)` function"
+        | "`atanh(f32x3)` function"
+        | "`atanh(f32x4)` function" => |p| constants::atanh(p[0]),
+        "`atan2(f32, f32)` function"
+        | "`atan2(f32x2, f32x2)` function"
+        | "`atan2(f32x3, f32x3)` function"
+        | "`atan2(f32x4, f32x4)` function" => |p| constants::atan2(p[0], p[1]),
+        "`cos(f32)` function"
+        | "`cos(f32x2)` function"
+        | "`cos(f32x3)` function"
+        | "`cos(f32x4)` function" => |p| constants::cos(p[0]),
+        "`cosh(f32)` function"
+        | "`cosh(f32x2)` function"
+        | "`cosh(f32x3)` function"
+        | "`cosh(f32x4)` function" => |p| constants::cosh(p[0]),
+        "`sin(f32)` function"
+        | "`sin(f32x2)` function"
+        | "`sin(f32x3)` function"
+        | "`sin(f32x4)` function" => |p| constants::sin(p[0]),
+        "`sinh(f32)` function"
+        | "`sinh(f32x2)` function"
+        | "`sinh(f32x3)` function"
+        | "`sinh(f32x4)` function" => |p| constants::sinh(p[0]),
+        "`tan(f32)` function"
+        | "`tan(f32x2)` function"
+        | "`tan(f32x3)` function"
+        | "`tan(f32x4)` function" => |p| constants::tan(p[0]),
+        "`tanh(f32)` function"
+        | "`tanh(f32x2)` function"
+        | "`tanh(f32x3)` function"
+        | "`tanh(f32x4)` function" => |p| constants::tanh(p[0]),
+        "`degrees(f32)` function"
+        | "`degrees(f32x2)` function"
+        | "`degrees(f32x3)` function"
+        | "`degrees(f32x4)` function" => |p| constants::degrees(p[0]),
+        "`radians(f32)` function"
+        | "`radians(f32x2)` function"
+        | "`radians(f32x3)` function"
+        | "`radians(f32x4)` function" => |p| constants::radians(p[0]),
+        _ => None?,
+    })
+}
+
+fn matrix_runner(fn_key: &str) -> Option<fn(params: &[&ConstantValue]) -> ConstantData> {
+    Some(match fn_key {
+        "`f32x2x2()` function" => |_| zero_matrix(2, 2),
+        "`f32x2x3()` function" => |_| zero_matrix(2, 3),
+        "`f32x2x4()` function" => |_| zero_matrix(2, 4),
+        "`f32x3x2()` function" => |_| zero_matrix(3, 2),
+        "`f32x3x3()` function" => |_| zero_matrix(3, 3),
+        "`f32x3x4()` function" => |_| zero_matrix(3, 4),
+        "`f32x4x2()` function" => |_| zero_matrix(4, 2),
+        "`f32x4x3()` function" => |_| zero_matrix(4, 3),
+        "`f32x4x4()` function" => |_| zero_matrix(4, 4),
+        "`f32x2x2(f32x2, f32x2)` function"
+        | "`f32x2x3(f32x3, f32x3)` function"
+        | "`f32x2x4(f32x4, f32x4)` function"
+        | "`f32x3x2(f32x2, f32x2, f32x2)` function"
+        | "`f32x3x3(f32x3, f32x3, f32x3)` function"
+        | "`f32x3x4(f32x4, f32x4, f32x4)` function"
+        | "`f32x4x2(f32x2, f32x2, f32x2, f32x2)` function"
+        | "`f32x4x3(f32x3, f32x3, f32x3, f32x3)` function"
+        | "`f32x4x4(f32x4, f32x4, f32x4, f32x4)` function" => {
+            |p| ConstantData::Array(p.iter().map(|&column| column.clone()).collect())
+        }
+        "`__mul__(f32, f32x2x2)` function"
+        | "`__mul__(f32, f32x2x3)` function"
+        | "`__mul__(f32, f32x2x4)` function"
+        | "`__mul__(f32, f32x3x2)` function"
+        | "`__mul__(f32, f32x3x3)` function"
+        | "`__mul__(f32, f32x3x4)` function"
+        | "`__mul__(f32, f32x4x2)` function"
+        | "`__mul__(f32, f32x4x3)` function"
+        | "`__mul__(f32, f32x4x4)` function" => |p| scale_matrix(p[1], p[0]),
+        "`__mul__(f32x2x2, f32)` function"
+        | "`__mul__(f32x2x3, f32)` function"
+        | "`__mul__(f32x2x4, f32)` function"
+        | "`__mul__(f32x3x2, f32)` function"
+        | "`__mul__(f32x3x3, f32)` function"
+        | "`__mul__(f32x3x4, f32)` function"
+        | "`__mul__(f32x4x2, f32)` function"
+        | "`__mul__(f32x4x3, f32)` function"
+        | "`__mul__(f32x4x4, f32)` function" => |p| scale_matrix(p[0], p[1]),
+        "`__mul__(f32x2x2, f32x2)` function"
+        | "`__mul__(f32x2x3, f32x2)` function"
+        | "`__mul__(f32x2x4, f32x2)` function"
+        | "`__mul__(f32x3x2, f32x3)` function"
+        | "`__mul__(f32x3x3, f32x3)` function"
+        | "`__mul__(f32x3x4, f32x3)` function"
+        | "`__mul__(f32x4x2, f32x4)` function"
+        | "`__mul__(f32x4x3, f32x4)` function"
+        | "`__mul__(f32x4x4, f32x4)` function" => |p| mul_matrix_vector(p[0], p[1]),
+        "`__mul__(f32x2x2, f32x2x2)` function"
+        | "`__mul__(f32x2x2, f32x3x2)` function"
+        | "`__mul__(f32x2x2, f32x4x2)` function"
+        | "`__mul__(f32x2x3, f32x2x2)` function"
+        | "`__mul__(f32x2x3, f32x3x2)` function"
+        | "`__mul__(f32x2x3, f32x4x2)` function"
+        | "`__mul__(f32x2x4, f32x2x2)` function"
+        | "`__mul__(f32x2x4, f32x3x2)` function"
+        | "`__mul__(f32x2x4, f32x4x2)` function"
+        | "`__mul__(f32x3x2, f32x2x3)` function"
+        | "`__mul__(f32x3x2, f32x3x3)` function"
+        | "`__mul__(f32x3x2, f32x4x3)` function"
+        | "`__mul__(f32x3x3, f32x2x3)` function"
+        | "`__mul__(f32x3x3, f32x3x3)` function"
+        | "`__mul__(f32x3x3, f32x4x3)` function"
+        | "`__mul__(f32x3x4, f32x2x3)` function"
+        | "`__mul__(f32x3x4, f32x3x3)` function"
+        | "`__mul__(f32x3x4, f32x4x3)` function"
+        | "`__mul__(f32x4x2, f32x2x4)` function"
+        | "`__mul__(f32x4x2, f32x3x4)` function"
+        | "`__mul__(f32x4x2, f32x4x4)` function"
+        | "`__mul__(f32x4x3, f32x2x4)` function"
+        | "`__mul__(f32x4x3, f32x3x4)` function"
+        | "`__mul__(f32x4x3, f32x4x4)` function"
+        | "`__mul__(f32x4x4, f32x2x4)` function"
+        | "`__mul__(f32x4x4, f32x3x4)` function"
+        | "`__mul__(f32x4x4, f32x4x4)` function" => |p| mul_matrices(p[0], p[1]),
+        "`transpose(f32x2x2)` function"
+        | "`transpose(f32x2x3)` function"
+        | "`transpose(f32x2x4)` function"
+        | "`transpose(f32x3x2)` function"
+        | "`transpose(f32x3x3)` function"
+        | "`transpose(f32x3x4)` function"
+        | "`transpose(f32x4x2)` function"
+        | "`transpose(f32x4x3)` function"
+        | "`transpose(f32x4x4)` function" => |p| transpose(p[0]),
+        "`determinant(f32x2x2)` function"
+        | "`determinant(f32x3x3)` function"
+        | "`determinant(f32x4x4)` function" => {
+            |p| ConstantData::F32(determinant(&matrix_columns(p[0])))
+        }
+        _ => None?,
+    })
+}
+
 fn f32(value: f32) -> ConstantValue {
     ConstantValue {
         transpiled_type_name: "f32".into(),
@@ -149,7 +312,125 @@ fn to_f32(value: &ConstantValue) -> ConstantValue {
             ConstantData::F32(value) => ConstantData::F32(value),
             ConstantData::I32(value) => ConstantData::F32(value as f32),
             ConstantData::U32(value) => ConstantData::F32(value as f32),
-            ConstantData::StructFields(_) => unreachable!("unsupported native conversion"),
+            ConstantData::StructFields(_) | ConstantData::Array(_) => {
+                unreachable!("unsupported native conversion")
+            }
+        },
+    }
+}
+
+fn zero_matrix(column_count: usize, row_count: usize) -> ConstantData {
+    matrix(&vec![vec![0.; row_count]; column_count])
+}
+
+fn scale_matrix(matrix_value: &ConstantValue, factor: &ConstantValue) -> ConstantData {
+    let factor = f32_value(factor);
+    let columns = matrix_columns(matrix_value)
+        .into_iter()
+        .map(|column| column.into_iter().map(|item| item * factor).collect())
+        .collect::<Vec<_>>();
+    matrix(&columns)
+}
+
+fn mul_matrix_vector(matrix_value: &ConstantValue, vector_value: &ConstantValue) -> ConstantData {
+    vector(&matrix_vector_product(
+        &matrix_columns(matrix_value),
+        &vector_components(vector_value),
+    ))
+    .data
+}
+
+fn mul_matrices(left: &ConstantValue, right: &ConstantValue) -> ConstantData {
+    let left = matrix_columns(left);
+    let columns = matrix_columns(right)
+        .iter()
+        .map(|column| matrix_vector_product(&left, column))
+        .collect::<Vec<_>>();
+    matrix(&columns)
+}
+
+fn transpose(matrix_value: &ConstantValue) -> ConstantData {
+    let columns = matrix_columns(matrix_value);
+    let rows = (0..columns[0].len())
+        .map(|row| columns.iter().map(|column| column[row]).collect())
+        .collect::<Vec<_>>();
+    matrix(&rows)
+}
+
+fn matrix_vector_product(columns: &[Vec<f32>], vector: &[f32]) -> Vec<f32> {
+    (0..columns[0].len())
+        .map(|row| {
+            columns
+                .iter()
+                .zip(vector)
+                .map(|(column, factor)| column[row] * factor)
+                .sum()
+        })
+        .collect()
+}
+
+// Laplace expansion along the first column.
+fn determinant(columns: &[Vec<f32>]) -> f32 {
+    if columns.len() == 1 {
+        return columns[0][0];
+    }
+    (0..columns.len())
+        .map(|row| {
+            let minor = columns[1..]
+                .iter()
+                .map(|column| {
+                    column
+                        .iter()
+                        .enumerate()
+                        .filter(|&(minor_row, _)| minor_row != row)
+                        .map(|(_, &item)| item)
+                        .collect()
+                })
+                .collect::<Vec<_>>();
+            let sign = if row % 2 == 0 { 1. } else { -1. };
+            sign * columns[0][row] * determinant(&minor)
+        })
+        .sum()
+}
+
+fn matrix(columns: &[Vec<f32>]) -> ConstantData {
+    ConstantData::Array(columns.iter().map(|column| vector(column)).collect())
+}
+
+fn vector(components: &[f32]) -> ConstantValue {
+    ConstantValue {
+        transpiled_type_name: format!("vec{}<f32>", components.len()),
+        data: match *components {
+            [x, y] => constants::vec2(f32(x), f32(y)),
+            [x, y, z] => constants::vec3(f32(x), f32(y), f32(z)),
+            [x, y, z, w] => constants::vec4(f32(x), f32(y), f32(z), f32(w)),
+            _ => unreachable!("invalid const vector size"),
         },
     }
 }
+
+fn matrix_columns(value: &ConstantValue) -> Vec<Vec<f32>> {
+    if let ConstantData::Array(columns) = &value.data {
+        columns.iter().map(vector_components).collect()
+    } else {
+        unreachable!("const matrix is not an array")
+    }
+}
+
+fn vector_components(value: &ConstantValue) -> Vec<f32> {
+    value
+        .data
+        .fields()
+        .iter()
+        .filter(|field| !field.is_alias)
+        .map(|field| f32_value(&field.value))
+        .collect()
+}
+
+fn f32_value(value: &ConstantValue) -> f32 {
+    if let ConstantData::F32(value) = value.data {
+        value
+    } else {
+        unreachable!("const value is not a f32")
+    }
+}
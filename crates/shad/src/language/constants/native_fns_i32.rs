@@ -139,6 +139,30 @@ fn operator_runner(fn_key: &str) -> Option<fn(params: &[&ConstantValue]) -> Cons
         | "`__neg__(i32x2)` function"
         | "`__neg__(i32x3)` function"
         | "`__neg__(i32x4)` function" => |p| constants::neg(p[0]),
+        "`__band__(i32, i32)` function"
+        | "`__band__(i32x2, i32x2)` function"
+        | "`__band__(i32x3, i32x3)` function"
+        | "`__band__(i32x4, i32x4)` function" => |p| constants::band(p[0], p[1]),
+        "`__bor__(i32, i32)` function"
+        | "`__bor__(i32x2, i32x2)` function"
+        | "`__bor__(i32x3, i32x3)` function"
+        | "`__bor__(i32x4, i32x4)` function" => |p| constants::bor(p[0], p[1]),
+        "`__xor__(i32, i32)` function"
+        | "`__xor__(i32x2, i32x2)` function"
+        | "`__xor__(i32x3, i32x3)` function"
+        | "`__xor__(i32x4, i32x4)` function" => |p| constants::xor(p[0], p[1]),
+        "`__shl__(i32, u32)` function"
+        | "`__shl__(i32x2, u32x2)` function"
+        | "`__shl__(i32x3, u32x3)` function"
+        | "`__shl__(i32x4, u32x4)` function" => |p| constants::shl(p[0], p[1]),
+        "`__shr__(i32, u32)` function"
+        | "`__shr__(i32x2, u32x2)` function"
+        | "`__shr__(i32x3, u32x3)` function"
+        | "`__shr__(i32x4, u32x4)` function" => |p| constants::shr(p[0], p[1]),
+        "`__bnot__(i32)` function"
+        | "`__bnot__(i32x2)` function"
+        | "`__bnot__(i32x3)` function"
+        | "`__bnot__(i32x4)` function" => |p| constants::bnot(p[0]),
         _ => None?,
     })
 }
@@ -163,7 +187,9 @@ fn to_i32(value: &ConstantValue) -> ConstantValue {
             ConstantData::F32(value) => ConstantData::I32(value as i32),
             ConstantData::I32(value) => ConstantData::I32(value),
             ConstantData::U32(value) => ConstantData::I32(value as i32),
-            ConstantData::StructFields(_) => unreachable!("unsupported native conversion"),
+            ConstantData::StructFields(_) | ConstantData::Array(_) => {
+                unreachable!("unsupported native conversion")
+            }
         },
     }
 }
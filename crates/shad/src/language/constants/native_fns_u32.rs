@@ -135,6 +135,30 @@ fn operator_runner(fn_key: &str) -> Option<fn(params: &[&ConstantValue]) -> Cons
         | "`__ne__(u32x2, u32x2)` function"
         | "`__ne__(u32x3, u32x3)` function"
         | "`__ne__(u32x4, u32x4)` function" => |p| constants::ne(p[0], p[1]),
+        "`__band__(u32, u32)` function"
+        | "`__band__(u32x2, u32x2)` function"
+        | "`__band__(u32x3, u32x3)` function"
+        | "`__band__(u32x4, u32x4)` function" => |p| constants::band(p[0], p[1]),
+        "`__bor__(u32, u32)` function"
+        | "`__bor__(u32x2, u32x2)` function"
+        | "`__bor__(u32x3, u32x3)` function"
+        | "`__bor__(u32x4, u32x4)` function" => |p| constants::bor(p[0], p[1]),
+        "`__xor__(u32, u32)` function"
+        | "`__xor__(u32x2, u32x2)` function"
+        | "`__xor__(u32x3, u32x3)` function"
+        | "`__xor__(u32x4, u32x4)` function" => |p| constants::xor(p[0], p[1]),
+        "`__shl__(u32, u32)` function"
+        | "`__shl__(u32x2, u32x2)` function"
+        | "`__shl__(u32x3, u32x3)` function"
+        | "`__shl__(u32x4, u32x4)` function" => |p| constants::shl(p[0], p[1]),
+        "`__shr__(u32, u32)` function"
+        | "`__shr__(u32x2, u32x2)` function"
+        | "`__shr__(u32x3, u32x3)` function"
+        | "`__shr__(u32x4, u32x4)` function" => |p| constants::shr(p[0], p[1]),
+        "`__bnot__(u32)` function"
+        | "`__bnot__(u32x2)` function"
+        | "`__bnot__(u32x3)` function"
+        | "`__bnot__(u32x4)` function" => |p| constants::bnot(p[0]),
         _ => None?,
     })
 }
@@ -159,7 +183,9 @@ fn to_u32(value: &ConstantValue) -> ConstantValue {
             ConstantData::F32(value) => ConstantData::U32(value as u32),
             ConstantData::I32(value) => ConstantData::U32(value as u32),
             ConstantData::U32(value) => ConstantData::U32(value),
-            ConstantData::StructFields(_) => unreachable!("unsupported native conversion"),
+            ConstantData::StructFields(_) | ConstantData::Array(_) => {
+                unreachable!("unsupported native conversion")
+            }
         },
     }
 }
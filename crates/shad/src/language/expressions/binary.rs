@@ -1,21 +1,36 @@
 use crate::compilation::constant::{ConstantContext, ConstantData, ConstantValue};
 use crate::compilation::index::NodeIndex;
 use crate::compilation::node::{
-    choice, sequence, transform, GenericArgs, Node, NodeConfig, NodeRef, NodeSource, Repeated,
+    choice, sequence, transform, GenericArgs, Node, NodeConfig, NodeProps, NodeRef, NodeSource,
+    Repeated,
 };
+use crate::compilation::parsing;
+use crate::compilation::parsing::ParsingContext;
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
-use crate::language::expressions::chain::ChainExpr;
-use crate::language::expressions::fn_call::transpile_fn_call;
+use crate::compilation::PRELUDE_PATH;
+use crate::language::expressions::chain::{ChainExpr, TransformedChainExpr};
+use crate::language::expressions::fn_call;
+use crate::language::expressions::fn_call::{transpile_fn_call, FnCallExpr};
+use crate::language::expressions::simple;
+use crate::language::expressions::simple::{LenofExpr, VarIdentExpr};
+use crate::language::items::enum_;
 use crate::language::items::fn_;
 use crate::language::keywords::{
-    AndSymbol, CloseAngleBracketSymbol, DoubleEqSymbol, GreaterEqSymbol, HyphenSymbol,
-    LessEqSymbol, NotEqSymbol, OpenAngleBracketSymbol, OrSymbol, PercentSymbol, PlusSymbol,
-    SlashSymbol, StarSymbol,
+    AmpersandSymbol, AndSymbol, CaretSymbol, CloseAngleBracketSymbol,
+    DoubleCloseAngleBracketSymbol, DoubleEqSymbol, DoubleOpenAngleBracketSymbol, GreaterEqSymbol,
+    HyphenSymbol, LessEqSymbol, NotEqSymbol, OpenAngleBracketSymbol, OrSymbol, PercentSymbol,
+    PipeSymbol, PlusSymbol, SlashSymbol, StarSymbol,
 };
 use crate::language::transformations;
 use crate::language::validations;
 use crate::language::{constants, sources};
+use crate::ParsingError;
+use itertools::Itertools;
+use std::mem;
+use std::ops::Deref;
+use std::path::Path;
+use std::rc::Rc;
 
 transform!(
     MaybeBinaryExpr,
@@ -25,6 +40,34 @@ transform!(
 );
 
 impl MaybeBinaryExpr {
+    pub(crate) fn as_chain(&self) -> Option<&TransformedChainExpr> {
+        match self {
+            Self::Parsed(expr) if expr.right.iter().len() == 0 => expr.left.as_transformed(),
+            Self::Parsed(_) | Self::Transformed(_) => None,
+        }
+    }
+
+    pub(crate) fn as_var_ident(&self) -> Option<&VarIdentExpr> {
+        match self {
+            Self::Parsed(expr) if expr.right.iter().len() == 0 => expr.left.as_var_ident(),
+            Self::Parsed(_) | Self::Transformed(_) => None,
+        }
+    }
+
+    pub(crate) fn as_lenof(&self) -> Option<&LenofExpr> {
+        match self {
+            Self::Parsed(expr) if expr.right.iter().len() == 0 => expr.left.as_lenof(),
+            Self::Parsed(_) | Self::Transformed(_) => None,
+        }
+    }
+
+    pub(crate) fn as_fn_call(&self) -> Option<&FnCallExpr> {
+        match self {
+            Self::Parsed(expr) if expr.right.iter().len() == 0 => expr.left.as_fn_call(),
+            Self::Parsed(_) | Self::Transformed(_) => None,
+        }
+    }
+
     pub(crate) fn parse_const_i32(&self, index: &NodeIndex) -> i32 {
         let mut ctx = ConstantContext::new(index);
         if let Some(ConstantValue {
@@ -38,12 +81,11 @@ impl MaybeBinaryExpr {
         }
     }
 
-    pub(crate) fn parse_const_u32(&self, index: &NodeIndex) -> u32 {
-        let mut ctx = ConstantContext::new(index);
+    pub(crate) fn parse_const_u32(&self, ctx: &mut ConstantContext<'_>) -> u32 {
         if let Some(ConstantValue {
             data: ConstantData::U32(value),
             ..
-        }) = self.evaluate_constant(&mut ctx)
+        }) = self.evaluate_constant(ctx)
         {
             value
         } else {
@@ -52,6 +94,95 @@ impl MaybeBinaryExpr {
     }
 }
 
+/// An expression directly followed by a block, like an `if` condition.
+///
+/// The expression stops before the first `{` not nested in parentheses, unless the expression
+/// parsed without this limit is still followed by a block, like in `if Wrapper { true }.value {}`.
+#[derive(Debug)]
+pub(crate) struct BlockHeaderExpr {
+    pub(crate) expr: Rc<MaybeBinaryExpr>,
+}
+
+impl Deref for BlockHeaderExpr {
+    type Target = NodeProps;
+
+    fn deref(&self) -> &Self::Target {
+        &self.expr
+    }
+}
+
+impl NodeConfig for BlockHeaderExpr {
+    fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
+        self.expr.is_ref(index)
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.expr.type_(index)
+    }
+
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.expr.invalid_constant(index)
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        self.expr.evaluate_constant(ctx)
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        self.expr.transpile(ctx, generic_args)
+    }
+}
+
+impl Node for BlockHeaderExpr {
+    fn parse(ctx: &mut ParsingContext<'_>) -> Result<Self, ParsingError>
+    where
+        Self: Sized,
+    {
+        ctx.parse_spaces();
+        let block_start_offset = parsing::block_start_offset(ctx);
+        let mut unbounded_ctx = ctx.clone();
+        if let Ok(expr) = MaybeBinaryExpr::parse(&mut unbounded_ctx) {
+            if unbounded_ctx.offset > block_start_offset
+                && unbounded_ctx.code[unbounded_ctx.offset..]
+                    .trim_start()
+                    .starts_with('{')
+            {
+                ctx.apply(&unbounded_ctx);
+                return Ok(Self {
+                    expr: Rc::new(expr),
+                });
+            }
+        }
+        let mut local_ctx = ParsingContext {
+            code: &ctx.code[..block_start_offset],
+            ..ctx.clone()
+        };
+        let expr = MaybeBinaryExpr::parse(&mut local_ctx)?;
+        ctx.apply(&local_ctx);
+        Ok(Self {
+            expr: Rc::new(expr),
+        })
+    }
+
+    fn index(&self, index: &mut NodeIndex) {
+        debug_assert!(self.expr.key().is_none());
+        self.expr.index(index);
+    }
+
+    fn validate_nested(&self, ctx: &mut ValidationContext<'_>) {
+        self.expr.validate(ctx);
+        self.expr.validate_nested(ctx);
+    }
+
+    fn direct_nested_sources<'a>(&'a self, index: &'a NodeIndex) -> Vec<NodeSource<'a>> {
+        self.expr.direct_nested_sources(index)
+    }
+}
+
 sequence!(
     struct ParsedMaybeBinaryExpr {
         left: ChainExpr,
@@ -101,49 +232,58 @@ sequence!(
 
 impl NodeConfig for BinaryExpr {
     fn source_key(&self, index: &NodeIndex) -> Option<String> {
-        let name = match &*self.operator {
-            BinaryOperator::Add(_) => "__add__",
-            BinaryOperator::Sub(_) => "__sub__",
-            BinaryOperator::Mul(_) => "__mul__",
-            BinaryOperator::Div(_) => "__div__",
-            BinaryOperator::Mod(_) => "__mod__",
-            BinaryOperator::LessEq(_) => "__le__",
-            BinaryOperator::GreaterEq(_) => "__ge__",
-            BinaryOperator::Less(_) => "__lt__",
-            BinaryOperator::Greater(_) => "__gt__",
-            BinaryOperator::Eq(_) => "__eq__",
-            BinaryOperator::NotEq(_) => "__ne__",
-            BinaryOperator::And(_) => "__and__",
-            BinaryOperator::Or(_) => "__or__",
-        };
+        if self.enum_comparison_type(index).is_some() {
+            return None;
+        }
         Some(sources::fn_key_from_operator(
-            name,
+            self.fn_name(),
             [self.left.type_(index)?, self.right.type_(index)?],
+            index,
         ))
     }
 
     fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
-        let source = index.search(self, &self.source_key(index)?, sources::fn_criteria())?;
-        Some(NodeSource {
-            node: NodeRef::Other(source),
-            generic_args: vec![],
-        })
+        if self.enum_comparison_type(index).is_some() {
+            return None;
+        }
+        fn_call::fn_source(
+            self,
+            self.fn_name(),
+            vec![self.left.type_(index)?, self.right.type_(index)?],
+            index,
+        )
     }
 
     fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
+        if self.enum_comparison_type(index).is_some() {
+            return Some(false);
+        }
         self.source(index)
             .and_then(|source| source.node().is_ref(index))
     }
 
     fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
-        self.source(index)?.node().type_(index)
+        if self.enum_comparison_type(index).is_some() {
+            return Some(NodeSource {
+                node: NodeRef::Type(simple::bool_type(self, index)),
+                generic_args: vec![],
+            });
+        }
+        fn_call::fn_type(&self.source(index)?, index)
     }
 
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
         validations::check_missing_source(self, ctx);
+        if let Some(source) = self.source(ctx.index) {
+            fn_call::check_generic_constraints(self, &source, ctx);
+            fn_call::check_copied_args(&source, [&*self.left, &*self.right].into_iter(), ctx);
+        }
     }
 
     fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        if self.enum_comparison_type(index).is_some() {
+            return Some(self);
+        }
         (!fn_::is_const(self.source(index)?.node()))
             .then_some(self as _)
             .or_else(|| self.left.invalid_constant(index))
@@ -151,12 +291,28 @@ impl NodeConfig for BinaryExpr {
     }
 
     fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
-        let fn_ = self.source(ctx.index)?.node();
-        let args = constants::evaluate_fn_args(fn_, [&*self.left, &*self.right].into_iter(), ctx);
-        ctx.start_fn(args);
-        let value = fn_.evaluate_constant(ctx);
-        ctx.end_fn();
-        value
+        if self.is_short_circuit(ctx.index) {
+            let left = self.left.evaluate_constant(ctx)?;
+            let is_right_skipped = match left.data {
+                ConstantData::Bool(value) => {
+                    value == matches!(&*self.operator, BinaryOperator::Or(_))
+                }
+                ConstantData::F32(_)
+                | ConstantData::I32(_)
+                | ConstantData::U32(_)
+                | ConstantData::StructFields(_)
+                | ConstantData::Array(_) => unreachable!("invalid const boolean operand"),
+            };
+            return if is_right_skipped {
+                Some(left)
+            } else {
+                self.right.evaluate_constant(ctx)
+            };
+        }
+        let fn_ = ctx.specialized(&self.source(ctx.index)?);
+        let args = [&*self.left, &*self.right].into_iter();
+        let args = constants::evaluate_fn_args(fn_.node(), args, ctx)?;
+        ctx.evaluate_fn(&fn_, args)
     }
 
     fn transpile(
@@ -164,6 +320,12 @@ impl NodeConfig for BinaryExpr {
         ctx: &mut TranspilationContext<'_>,
         generic_args: &GenericArgs<'_>,
     ) -> String {
+        if let Some(type_) = self.enum_comparison_type(ctx.index) {
+            return self.transpile_enum_comparison(ctx, &type_, generic_args);
+        }
+        if self.is_short_circuit(ctx.index) {
+            return self.transpile_short_circuit(ctx, generic_args);
+        }
         let source = self
             .source(ctx.index)
             .expect("internal error: fn call source not found");
@@ -176,6 +338,102 @@ impl NodeConfig for BinaryExpr {
     }
 }
 
+impl BinaryExpr {
+    // Only the prelude `&&` and `||` operators skip the evaluation of the right operand.
+    fn is_short_circuit(&self, index: &NodeIndex) -> bool {
+        matches!(
+            &*self.operator,
+            BinaryOperator::And(_) | BinaryOperator::Or(_)
+        ) && self
+            .source(index)
+            .is_some_and(|source| source.node().path == Path::new(PRELUDE_PATH))
+    }
+
+    fn transpile_short_circuit(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let left = self.left.transpile(ctx, generic_args);
+        let var_name = format!("_{}", ctx.next_node_id());
+        ctx.generated_stmts
+            .push(format!("var {var_name} = {left};"));
+        let left_stmts = mem::take(&mut ctx.generated_stmts);
+        let right = self.right.transpile(ctx, generic_args);
+        let right_stmts = mem::replace(&mut ctx.generated_stmts, left_stmts)
+            .into_iter()
+            .chain([format!("{var_name} = {right};")])
+            .join("\n");
+        let negation = if matches!(&*self.operator, BinaryOperator::Or(_)) {
+            "!"
+        } else {
+            ""
+        };
+        ctx.generated_stmts.push(format!(
+            "if {negation}bool({var_name}) {{\n{right_stmts}\n}}"
+        ));
+        var_name
+    }
+
+    // Enums are compared with a generated function instead of an `__eq__` or `__ne__` function.
+    fn enum_comparison_type<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        if !matches!(
+            &*self.operator,
+            BinaryOperator::Eq(_) | BinaryOperator::NotEq(_)
+        ) {
+            return None;
+        }
+        let left_type = self.left.type_(index)?;
+        let right_type = self.right.type_(index)?;
+        (enum_::enum_item(&left_type).is_some()
+            && left_type.are_same_types(&right_type, index) == Some(true))
+        .then_some(left_type)
+    }
+
+    fn transpile_enum_comparison(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        type_: &NodeSource<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let type_ = type_.specialized(generic_args, ctx.index);
+        let fn_name = enum_::enum_item(&type_)
+            .expect("internal error: invalid enum type")
+            .transpiled_eq_fn_name(ctx.index, &type_.generic_args);
+        let left = self.left.transpile(ctx, generic_args);
+        let right = self.right.transpile(ctx, generic_args);
+        let negation = if matches!(&*self.operator, BinaryOperator::NotEq(_)) {
+            "!"
+        } else {
+            ""
+        };
+        format!("u32({negation}{fn_name}({left}, {right}))")
+    }
+
+    fn fn_name(&self) -> &'static str {
+        match &*self.operator {
+            BinaryOperator::Add(_) => "__add__",
+            BinaryOperator::Sub(_) => "__sub__",
+            BinaryOperator::Mul(_) => "__mul__",
+            BinaryOperator::Div(_) => "__div__",
+            BinaryOperator::Mod(_) => "__mod__",
+            BinaryOperator::LessEq(_) => "__le__",
+            BinaryOperator::GreaterEq(_) => "__ge__",
+            BinaryOperator::Less(_) => "__lt__",
+            BinaryOperator::Greater(_) => "__gt__",
+            BinaryOperator::Eq(_) => "__eq__",
+            BinaryOperator::NotEq(_) => "__ne__",
+            BinaryOperator::And(_) => "__and__",
+            BinaryOperator::Or(_) => "__or__",
+            BinaryOperator::BitAnd(_) => "__band__",
+            BinaryOperator::BitOr(_) => "__bor__",
+            BinaryOperator::BitXor(_) => "__xor__",
+            BinaryOperator::Shl(_) => "__shl__",
+            BinaryOperator::Shr(_) => "__shr__",
+        }
+    }
+}
+
 sequence!(
     struct ParsedBinaryRight {
         operator: BinaryOperator,
@@ -186,6 +444,7 @@ sequence!(
 
 impl NodeConfig for ParsedBinaryRight {}
 
+// Operators starting with the symbol of another operator must be placed first.
 choice!(
     enum BinaryOperator {
         Add(PlusSymbol),
@@ -193,6 +452,8 @@ choice!(
         Mul(StarSymbol),
         Div(SlashSymbol),
         Mod(PercentSymbol),
+        Shl(DoubleOpenAngleBracketSymbol),
+        Shr(DoubleCloseAngleBracketSymbol),
         LessEq(LessEqSymbol),
         GreaterEq(GreaterEqSymbol),
         Less(OpenAngleBracketSymbol),
@@ -201,5 +462,8 @@ choice!(
         NotEq(NotEqSymbol),
         And(AndSymbol),
         Or(OrSymbol),
+        BitAnd(AmpersandSymbol),
+        BitOr(PipeSymbol),
+        BitXor(CaretSymbol),
     }
 );
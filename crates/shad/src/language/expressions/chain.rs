@@ -1,4 +1,6 @@
-use crate::compilation::constant::{ConstantContext, ConstantData, ConstantValue};
+use crate::compilation::constant::{
+    ConstantContext, ConstantData, ConstantInterruption, ConstantValue,
+};
 use crate::compilation::index::NodeIndex;
 use crate::compilation::node::{
     choice, sequence, transform, GenericArgs, Node, NodeConfig, NodeRef, NodeSource, Repeated,
@@ -6,20 +8,26 @@ use crate::compilation::node::{
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
 use crate::language::expressions::binary::MaybeBinaryExpr;
-use crate::language::expressions::constructor::ConstructorExpr;
+use crate::language::expressions::constructor::{ConstructorExpr, EnumVariantExpr};
+use crate::language::expressions::fn_call;
 use crate::language::expressions::fn_call::{
     check_arg_names, transpile_fn_call, FnArgGroup, FnCallExpr,
 };
 use crate::language::expressions::simple::{
-    FalseLiteral, ParenthesizedExpr, TrueLiteral, TypeOperationExpr, VarIdentExpr,
+    FalseLiteral, LenofExpr, ParenthesizedExpr, TrueLiteral, TypeOperationExpr, VarIdentExpr,
 };
 use crate::language::expressions::unary::UnaryExpr;
 use crate::language::items::fn_;
-use crate::language::keywords::{CloseParenthesisSymbol, DotSymbol, OpenParenthesisSymbol};
-use crate::language::patterns::{F32Literal, I32Literal, Ident, U32Literal};
+use crate::language::keywords::{
+    CloseParenthesisSymbol, CloseSquareBracketSymbol, DotSymbol, OpenParenthesisSymbol,
+    OpenSquareBracketSymbol,
+};
+use crate::language::patterns::{F32Literal, I32Literal, Ident, TupleFieldIndex, U32Literal};
 use crate::language::transformations;
+use crate::language::type_ref;
 use crate::language::validations;
 use crate::language::{constants, sources};
+use crate::ValidationError;
 use std::iter;
 
 transform!(
@@ -29,6 +37,67 @@ transform!(
     transformations::transform_chain_expr
 );
 
+impl ChainExpr {
+    pub(crate) fn as_transformed(&self) -> Option<&TransformedChainExpr> {
+        match self {
+            Self::Transformed(expr) => Some(expr),
+            Self::Parsed(_) => None,
+        }
+    }
+
+    pub(crate) fn as_var_ident(&self) -> Option<&VarIdentExpr> {
+        match self {
+            Self::Parsed(expr) => match &*expr.expr {
+                ChainPrefix::Var(var) => Some(var),
+                ChainPrefix::Parenthesized(expr) => expr.as_var_ident(),
+                ChainPrefix::True(_)
+                | ChainPrefix::False(_)
+                | ChainPrefix::F32(_)
+                | ChainPrefix::U32(_)
+                | ChainPrefix::I32(_)
+                | ChainPrefix::EnumVariant(_)
+                | ChainPrefix::FnCall(_)
+                | ChainPrefix::Constructor(_)
+                | ChainPrefix::Unary(_)
+                | ChainPrefix::TypeOperation(_)
+                | ChainPrefix::Lenof(_) => None,
+            },
+            Self::Transformed(expr) if expr.suffix.iter().len() == 0 => expr.expr.as_var_ident(),
+            Self::Transformed(_) => None,
+        }
+    }
+
+    pub(crate) fn as_lenof(&self) -> Option<&LenofExpr> {
+        match self {
+            Self::Parsed(expr) => match &*expr.expr {
+                ChainPrefix::Lenof(lenof) => Some(lenof),
+                ChainPrefix::Parenthesized(expr) => expr.as_lenof(),
+                ChainPrefix::True(_)
+                | ChainPrefix::False(_)
+                | ChainPrefix::F32(_)
+                | ChainPrefix::U32(_)
+                | ChainPrefix::I32(_)
+                | ChainPrefix::EnumVariant(_)
+                | ChainPrefix::FnCall(_)
+                | ChainPrefix::Constructor(_)
+                | ChainPrefix::Var(_)
+                | ChainPrefix::Unary(_)
+                | ChainPrefix::TypeOperation(_) => None,
+            },
+            Self::Transformed(expr) if expr.suffix.iter().len() == 0 => expr.expr.as_lenof(),
+            Self::Transformed(_) => None,
+        }
+    }
+
+    pub(crate) fn as_fn_call(&self) -> Option<&FnCallExpr> {
+        match self {
+            Self::Parsed(expr) => expr.expr.as_fn_call(),
+            Self::Transformed(expr) if expr.suffix.iter().len() == 0 => expr.expr.as_fn_call(),
+            Self::Transformed(_) => None,
+        }
+    }
+}
+
 sequence!(
     struct ParsedChainExpr {
         expr: ChainPrefix,
@@ -81,22 +150,24 @@ impl NodeConfig for TransformedChainExpr {
             ChainSuffix::FnCall(suffix) => {
                 sources::fn_key_from_args(&suffix.ident, self.args(suffix), index)
             }
+            ChainSuffix::StructField(_) if self.swizzle(index).is_some() => None,
             ChainSuffix::StructField(suffix) => {
                 let prefix_type_key = self.expr.type_(index)?.type_item()?.key()?;
                 let field_name = &suffix.ident.slice;
                 Some(format!("`{field_name}` field of {prefix_type_key}"))
             }
+            ChainSuffix::TupleField(_) | ChainSuffix::Index(_) => None,
         }
     }
 
     fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
         Some(match &**self.suffix.iter().next()? {
-            ChainSuffix::FnCall(_) => {
-                let fn_ = index.search(self, &self.source_key(index)?, sources::fn_criteria())?;
-                NodeSource {
-                    node: NodeRef::Other(fn_),
-                    generic_args: vec![],
-                }
+            ChainSuffix::FnCall(suffix) => {
+                let arg_types = self
+                    .args(suffix)
+                    .map(|arg| arg.type_(index))
+                    .collect::<Option<Vec<_>>>()?;
+                fn_call::fn_source(self, &suffix.ident.slice, arg_types, index)?
             }
             ChainSuffix::StructField(suffix) => {
                 let field = self
@@ -111,23 +182,51 @@ impl NodeConfig for TransformedChainExpr {
                     generic_args: vec![],
                 }
             }
+            ChainSuffix::TupleField(_) | ChainSuffix::Index(_) => return None,
         })
     }
 
     fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
-        if self.suffix.iter().next().is_some() {
-            self.source(index)
-                .and_then(|source| source.node().is_ref(index))
-        } else {
-            self.expr.is_ref(index)
+        match self.suffix.iter().next().map(|suffix| &**suffix) {
+            // Swizzles with several components cannot be referenced in WGSL.
+            Some(ChainSuffix::StructField(_)) if self.swizzle(index).is_some() => Some(false),
+            Some(ChainSuffix::FnCall(_) | ChainSuffix::StructField(_)) => self
+                .source(index)
+                .and_then(|source| source.node().is_ref(index)),
+            Some(ChainSuffix::TupleField(_) | ChainSuffix::Index(_)) | None => {
+                self.expr.is_ref(index)
+            }
         }
     }
 
     fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
-        if self.suffix.iter().next().is_some() {
-            self.source(index)?.node().type_(index)
-        } else {
-            self.expr.type_(index)
+        match self.suffix.iter().next().map(|suffix| &**suffix) {
+            Some(ChainSuffix::FnCall(_)) => fn_call::fn_type(&self.source(index)?, index),
+            Some(ChainSuffix::StructField(_)) if self.swizzle(index).is_some() => {
+                let swizzle = self.swizzle(index)?;
+                type_ref::swizzle_type(&self.expr.type_(index)?, swizzle, index)
+            }
+            Some(ChainSuffix::StructField(_)) => {
+                let struct_type = self.expr.type_(index)?;
+                Some(
+                    self.source(index)?
+                        .node()
+                        .type_(index)?
+                        .specialized(&struct_type.generic_args, index),
+                )
+            }
+            Some(ChainSuffix::TupleField(suffix)) => {
+                type_ref::tuple_item_types(&self.expr.type_(index)?)?
+                    .into_iter()
+                    .nth(suffix.index.position()?)?
+            }
+            Some(ChainSuffix::Index(_)) => {
+                let prefix_type = self.expr.type_(index)?;
+                type_ref::array_item_type(&prefix_type)
+                    .or_else(|| Some(type_ref::matrix_columns(&prefix_type, index)?.1))
+                    .or_else(|| Some(type_ref::vector_components(&prefix_type, index)?.1))
+            }
+            None => self.expr.type_(index),
         }
     }
 
@@ -146,9 +245,13 @@ impl NodeConfig for TransformedChainExpr {
                                 .map(|arg| arg.name.iter().map(|arg| &*arg.ident).next()),
                         );
                         check_arg_names(&source, arg_names, ctx);
+                        fn_call::check_generic_constraints(self, &source, ctx);
+                        fn_call::check_copied_args(&source, self.args(suffix), ctx);
                     }
                 }
                 ChainSuffix::StructField(_) => {}
+                ChainSuffix::TupleField(suffix) => self.validate_tuple_field(suffix, ctx),
+                ChainSuffix::Index(suffix) => self.validate_index(suffix, ctx),
             }
         }
         if self.suffix.iter().len() > 0 {
@@ -164,7 +267,8 @@ impl NodeConfig for TransformedChainExpr {
                         .args(suffix)
                         .find_map(|arg| arg.invalid_constant(index))
                         .or_else(|| (!fn_::is_const(self.source(index)?.node())).then_some(self)),
-                    ChainSuffix::StructField(_) => None,
+                    ChainSuffix::StructField(_) | ChainSuffix::TupleField(_) => None,
+                    ChainSuffix::Index(suffix) => suffix.index.invalid_constant(index),
                 }
             } else {
                 None
@@ -176,15 +280,20 @@ impl NodeConfig for TransformedChainExpr {
         if let Some(suffix) = self.suffix.iter().next() {
             match &**suffix {
                 ChainSuffix::FnCall(suffix) => {
-                    let fn_ = self.source(ctx.index)?.node();
-                    let args = constants::evaluate_fn_args(fn_, self.args(suffix), ctx);
-                    ctx.start_fn(args);
-                    let value = fn_.evaluate_constant(ctx);
-                    ctx.end_fn();
-                    value
+                    let fn_ = ctx.specialized(&self.source(ctx.index)?);
+                    let args = constants::evaluate_fn_args(fn_.node(), self.args(suffix), ctx)?;
+                    ctx.evaluate_fn(&fn_, args)
                 }
                 ChainSuffix::StructField(suffix) => {
                     let prefix = self.expr.evaluate_constant(ctx)?;
+                    if let Some(swizzle) = self.swizzle(ctx.index) {
+                        let type_ = ctx.specialized(&self.type_(ctx.index)?);
+                        return Some(constants::swizzle(
+                            &prefix,
+                            swizzle,
+                            type_.transpiled_type_name(ctx.index),
+                        ));
+                    }
                     match prefix.data {
                         ConstantData::StructFields(fields) => Some(
                             fields
@@ -196,11 +305,57 @@ impl NodeConfig for TransformedChainExpr {
                         ConstantData::F32(_)
                         | ConstantData::I32(_)
                         | ConstantData::U32(_)
-                        | ConstantData::Bool(_) => {
+                        | ConstantData::Bool(_)
+                        | ConstantData::Array(_) => {
                             unreachable!("const field used on non-struct value")
                         }
                     }
                 }
+                ChainSuffix::TupleField(suffix) => {
+                    let prefix = self.expr.evaluate_constant(ctx)?;
+                    match prefix.data {
+                        ConstantData::StructFields(fields) => Some(
+                            fields
+                                .iter()
+                                .find(|field| field.name == suffix.index.slice)?
+                                .value
+                                .clone(),
+                        ),
+                        ConstantData::F32(_)
+                        | ConstantData::I32(_)
+                        | ConstantData::U32(_)
+                        | ConstantData::Bool(_)
+                        | ConstantData::Array(_) => {
+                            unreachable!("const tuple field used on non-tuple value")
+                        }
+                    }
+                }
+                ChainSuffix::Index(suffix) => {
+                    let prefix = self.expr.evaluate_constant(ctx)?;
+                    let item_index = suffix.index.evaluate_constant(ctx)?;
+                    let item_index = constant_item_index(&item_index.data);
+                    let item = match &prefix.data {
+                        ConstantData::Array(items) => {
+                            item_index.and_then(|i| items.get(i).cloned())
+                        }
+                        ConstantData::StructFields(_) => item_index
+                            .and_then(|i| {
+                                let fields = prefix.data.fields().iter();
+                                fields.filter(|field| !field.is_alias).nth(i)
+                            })
+                            .map(|field| field.value.clone()),
+                        ConstantData::F32(_)
+                        | ConstantData::I32(_)
+                        | ConstantData::U32(_)
+                        | ConstantData::Bool(_) => {
+                            unreachable!("const index used on non-indexable value")
+                        }
+                    };
+                    if item.is_none() {
+                        ctx.interruption = Some(ConstantInterruption::IndexOutOfBounds);
+                    }
+                    item
+                }
             }
         } else {
             self.expr.evaluate_constant(ctx)
@@ -230,13 +385,121 @@ impl NodeConfig for TransformedChainExpr {
                 let suffix = type_.transpiled_field_name(&suffix.ident.slice);
                 format!("{prefix}.{suffix}")
             }
+            Some(ChainSuffix::TupleField(suffix)) => {
+                let prefix = self.expr.transpile(ctx, generic_args);
+                format!("{prefix}._{}", suffix.index.slice)
+            }
+            Some(ChainSuffix::Index(suffix)) => {
+                let prefix = self.expr.transpile(ctx, generic_args);
+                let index = suffix.index.transpile(ctx, generic_args);
+                format!("{prefix}[{index}]")
+            }
             None => self.expr.transpile(ctx, generic_args),
         }
     }
 }
 
 impl TransformedChainExpr {
-    fn args<'a>(
+    fn validate_tuple_field(&self, suffix: &AssociatedTupleField, ctx: &mut ValidationContext<'_>) {
+        let Some(prefix_type) = self.expr.type_(ctx.index) else {
+            return;
+        };
+        if prefix_type.is_no_return() {
+            return;
+        }
+        let Some(item_types) = type_ref::tuple_item_types(&prefix_type) else {
+            let type_name = prefix_type.name_or_no_return(ctx.index);
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.expr,
+                "invalid tuple field access",
+                Some(&format!("expression of type `{type_name}` is not a tuple")),
+                &[],
+            ));
+            return;
+        };
+        if suffix
+            .index
+            .position()
+            .is_none_or(|position| position >= item_types.len())
+        {
+            let type_name = prefix_type.name_or_no_return(ctx.index);
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*suffix.index,
+                "undefined tuple field",
+                Some(&format!(
+                    "tuple `{type_name}` has {} fields",
+                    item_types.len()
+                )),
+                &[],
+            ));
+        }
+    }
+
+    /// Returns the components of the swizzle applied on a vector, like `xy` or `bgr`.
+    ///
+    /// Single component accesses are not considered as swizzles, as they are vector fields.
+    pub(crate) fn swizzle(&self, index: &NodeIndex) -> Option<&str> {
+        let Some(ChainSuffix::StructField(suffix)) = self.suffix.iter().next().map(|s| &**s) else {
+            return None;
+        };
+        let swizzle = suffix.ident.slice.as_str();
+        type_ref::swizzle_type(&self.expr.type_(index)?, swizzle, index).map(|_| swizzle)
+    }
+
+    fn validate_index(&self, suffix: &IndexSuffix, ctx: &mut ValidationContext<'_>) {
+        let Some(prefix_type) = self.expr.type_(ctx.index) else {
+            return;
+        };
+        if prefix_type.is_no_return() {
+            return;
+        }
+        let (length, length_kind) = if type_ref::is_array(&prefix_type) {
+            (type_ref::array_length(&prefix_type), "array length")
+        } else if let Some((column_count, _)) = type_ref::matrix_columns(&prefix_type, ctx.index) {
+            (Some(column_count), "matrix column count")
+        } else if let Some((component_count, _)) =
+            type_ref::vector_components(&prefix_type, ctx.index)
+        {
+            (Some(component_count), "vector component count")
+        } else {
+            let type_name = prefix_type.name_or_no_return(ctx.index);
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.expr,
+                "invalid indexed expression",
+                Some(&format!(
+                    "expression of type `{type_name}` cannot be indexed"
+                )),
+                &[],
+            ));
+            return;
+        };
+        if !validations::check_integer_expr_type(&*suffix.index, ctx)
+            || suffix.index.invalid_constant(ctx.index).is_some()
+        {
+            return;
+        }
+        // Bounds of runtime-sized arrays are only known at runtime.
+        let Some(length) = length else {
+            return;
+        };
+        let mut constant_ctx = ConstantContext::new(ctx.index);
+        if let Some(item_index) = suffix.index.evaluate_constant(&mut constant_ctx) {
+            if constant_item_index(&item_index.data).is_none_or(|i| i >= length as usize) {
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    &*suffix.index,
+                    "index out of bounds",
+                    Some(&format!("{length_kind} is {length}")),
+                    &[],
+                ));
+            }
+        }
+    }
+
+    pub(crate) fn args<'a>(
         &'a self,
         suffix: &'a AssociatedFnCallSuffix,
     ) -> impl Iterator<Item = &'a MaybeBinaryExpr> {
@@ -258,11 +521,13 @@ choice!(
         U32(U32Literal),
         I32(I32Literal),
         FnCall(FnCallExpr),
+        EnumVariant(EnumVariantExpr),
         Constructor(ConstructorExpr),
         Var(VarIdentExpr),
         Unary(UnaryExpr),
         Parenthesized(ParenthesizedExpr),
         TypeOperation(TypeOperationExpr),
+        Lenof(LenofExpr),
     }
 );
 
@@ -270,6 +535,8 @@ choice!(
     enum ChainSuffix {
         FnCall(AssociatedFnCallSuffix),
         StructField(AssociatedStructField),
+        TupleField(AssociatedTupleField),
+        Index(IndexSuffix),
     }
 );
 
@@ -295,3 +562,33 @@ sequence!(
 );
 
 impl NodeConfig for AssociatedStructField {}
+
+sequence!(
+    #[allow(unused_mut)]
+    struct AssociatedTupleField {
+        dot: DotSymbol,
+        index: TupleFieldIndex,
+    }
+);
+
+impl NodeConfig for AssociatedTupleField {}
+
+sequence!(
+    struct IndexSuffix {
+        start: OpenSquareBracketSymbol,
+        #[force_error(true)]
+        index: MaybeBinaryExpr,
+        end: CloseSquareBracketSymbol,
+    }
+);
+
+impl NodeConfig for IndexSuffix {}
+
+#[allow(clippy::wildcard_enum_match_arm)]
+fn constant_item_index(data: &ConstantData) -> Option<usize> {
+    match data {
+        ConstantData::I32(value) => usize::try_from(*value).ok(),
+        ConstantData::U32(value) => usize::try_from(*value).ok(),
+        _ => unreachable!("const index is not an integer"),
+    }
+}
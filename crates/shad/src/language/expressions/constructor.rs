@@ -5,10 +5,16 @@ use crate::compilation::index::NodeIndex;
 use crate::compilation::node::{sequence, GenericArgs, Node, NodeConfig, NodeSource, Repeated};
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
+use crate::language::expressions::binary::MaybeBinaryExpr;
 use crate::language::expressions::fn_call::{FnArg, FnArgGroup};
-use crate::language::keywords::{CloseCurlyBracketSymbol, OpenCurlyBracketSymbol};
-use crate::language::type_ref::Type;
-use crate::language::validations;
+use crate::language::items::enum_;
+use crate::language::keywords::{
+    CloseCurlyBracketSymbol, CloseParenthesisSymbol, DoubleColonSymbol, OpenCurlyBracketSymbol,
+    OpenParenthesisSymbol,
+};
+use crate::language::patterns::Ident;
+use crate::language::type_ref::{ArrayType, NamedType, Type};
+use crate::language::{sources, type_ref, validations};
 use crate::ValidationError;
 use itertools::Itertools;
 
@@ -32,7 +38,14 @@ impl NodeConfig for ConstructorExpr {
     }
 
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
-        if let Some(type_item) = self.type_.item(ctx.index) {
+        if let Some(array_type) = self.type_.as_array() {
+            self.validate_array(array_type, ctx);
+            return;
+        }
+        if let Some(type_) = self.type_.type_(ctx.index) {
+            let type_item = type_
+                .type_item()
+                .expect("internal error: type reference must not be <no return>");
             if type_item.is_native() {
                 ctx.errors.push(ValidationError::error(
                     ctx,
@@ -43,6 +56,26 @@ impl NodeConfig for ConstructorExpr {
                 ));
                 return;
             }
+            if type_ref::tuple_item_types(&type_).is_some() {
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    self,
+                    "cannot call constructor for a tuple type",
+                    Some("constructor called here"),
+                    &[],
+                ));
+                return;
+            }
+            if enum_::enum_item(&type_).is_some() {
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    self,
+                    "cannot call constructor for an enum type",
+                    Some("constructor called here"),
+                    &[],
+                ));
+                return;
+            }
             if type_item.path != self.path
                 && type_item.fields().iter().any(|field| !field.is_public())
             {
@@ -61,8 +94,16 @@ impl NodeConfig for ConstructorExpr {
             if expected_field_count == actual_field_count {
                 for (arg, field) in self.args().zip(fields) {
                     let arg_name = arg.name.iter().next().map(|name| &*name.ident);
-                    validations::check_invalid_expr_type(field, arg, true, ctx);
+                    let field_type = field.specialized_type(ctx.index, &type_.generic_args);
+                    validations::check_invalid_expr_specialized_type(
+                        field,
+                        &field_type,
+                        arg,
+                        true,
+                        ctx,
+                    );
                     validations::check_arg_name(arg_name, &field.ident, ctx);
+                    validations::check_copyable_expr(arg, ctx);
                 }
             } else {
                 ctx.errors.push(ValidationError::error(
@@ -84,7 +125,17 @@ impl NodeConfig for ConstructorExpr {
     }
 
     fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
-        let type_ = self.type_.type_(ctx.index)?;
+        let type_ = ctx.specialized(&self.type_.type_(ctx.index)?);
+        if self.type_.as_array().is_some() {
+            return Some(ConstantValue {
+                transpiled_type_name: type_.transpiled_type_name(ctx.index),
+                data: ConstantData::Array(
+                    self.args()
+                        .map(|arg| arg.evaluate_constant(ctx))
+                        .collect::<Option<_>>()?,
+                ),
+            });
+        }
         Some(ConstantValue {
             transpiled_type_name: type_.transpiled_type_name(ctx.index),
             data: ConstantData::StructFields(
@@ -94,14 +145,14 @@ impl NodeConfig for ConstructorExpr {
                     .fields()
                     .iter()
                     .zip(self.args())
-                    .map(|(field, arg)| ConstantStructFieldData {
-                        name: field.ident.slice.clone(),
-                        value: arg
-                            .evaluate_constant(ctx)
-                            .expect("internal error: invalid const constructor arg"),
-                        is_alias: false,
+                    .map(|(field, arg)| {
+                        Some(ConstantStructFieldData {
+                            name: field.ident.slice.clone(),
+                            value: arg.evaluate_constant(ctx)?,
+                            is_alias: false,
+                        })
                     })
-                    .collect(),
+                    .collect::<Option<_>>()?,
             ),
         })
     }
@@ -115,6 +166,7 @@ impl NodeConfig for ConstructorExpr {
             .type_
             .type_(ctx.index)
             .expect("internal error: constructor source not found")
+            .specialized(generic_args, ctx.index)
             .transpiled_type_name(ctx.index);
         let args = self
             .args()
@@ -125,9 +177,156 @@ impl NodeConfig for ConstructorExpr {
 }
 
 impl ConstructorExpr {
-    fn args(&self) -> impl Iterator<Item = &FnArg> {
+    fn validate_array(&self, type_: &ArrayType, ctx: &mut ValidationContext<'_>) {
+        for arg in self.args() {
+            if let Some(arg_name) = arg.name.iter().next() {
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    &*arg_name.ident,
+                    "invalid argument name",
+                    Some("array items cannot be named"),
+                    &[],
+                ));
+            }
+            validations::check_invalid_expr_type(&*type_.item_type, arg, true, ctx);
+            validations::check_copyable_expr(arg, ctx);
+        }
+        if let Some(expected_item_count) = type_.length(ctx.index) {
+            let actual_item_count = self.args().count();
+            if expected_item_count as usize != actual_item_count {
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    self,
+                    "invalid number of items",
+                    Some(&format!("{actual_item_count} items specified here")),
+                    &[(type_, &format!("{expected_item_count} items expected"))],
+                ));
+            }
+        }
+    }
+
+    pub(crate) fn args(&self) -> impl Iterator<Item = &FnArg> {
         self.args
             .iter()
             .flat_map(|args| args.args().map(|arg| &**arg))
     }
 }
+
+sequence!(
+    struct EnumVariantExpr {
+        type_: NamedType,
+        separator: DoubleColonSymbol,
+        #[force_error(true)]
+        variant: Ident,
+        payload: Repeated<EnumVariantPayloadExpr, 0, 1>,
+    }
+);
+
+impl NodeConfig for EnumVariantExpr {
+    fn source_key(&self, index: &NodeIndex) -> Option<String> {
+        let type_ = self.type_.type_(index)?;
+        Some(sources::variant_key(
+            &self.variant,
+            &type_.name_or_no_return(index),
+        ))
+    }
+
+    fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        let type_ = self.type_.type_(index)?;
+        enum_::enum_item(&type_)?.variant(&self.variant.slice)?;
+        Some(type_)
+    }
+
+    fn is_ref(&self, _index: &NodeIndex) -> Option<bool> {
+        Some(false)
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.source(index)
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        validations::check_missing_source(self, ctx);
+        let Some(type_) = self.source(ctx.index) else {
+            return;
+        };
+        let variant = enum_::enum_item(&type_)
+            .and_then(|enum_| enum_.variant(&self.variant.slice))
+            .expect("internal error: variant not found");
+        let name = &variant.ident.slice;
+        match (
+            self.payload.iter().next(),
+            variant.payload_type(ctx.index, &type_.generic_args),
+        ) {
+            (Some(payload), Some(payload_type)) => {
+                validations::check_invalid_expr_specialized_type(
+                    variant,
+                    &payload_type,
+                    &*payload.value,
+                    true,
+                    ctx,
+                );
+                validations::check_copyable_expr(&*payload.value, ctx);
+            }
+            (None, Some(payload_type)) => {
+                let payload_type_name = payload_type.name_or_no_return(ctx.index);
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    self,
+                    "missing variant payload",
+                    Some(&format!(
+                        "`{name}` variant expects a payload of type `{payload_type_name}`"
+                    )),
+                    &[(variant, "variant defined here")],
+                ));
+            }
+            (Some(payload), None) if !variant.has_payload() => {
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    &**payload,
+                    "unexpected variant payload",
+                    Some(&format!("`{name}` variant has no payload")),
+                    &[(variant, "variant defined here")],
+                ));
+            }
+            (Some(_) | None, None) => {}
+        }
+    }
+
+    fn invalid_constant(&self, _index: &NodeIndex) -> Option<&dyn Node> {
+        Some(self)
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let type_ = self
+            .source(ctx.index)
+            .expect("internal error: enum variant source not found")
+            .specialized(generic_args, ctx.index);
+        let enum_ = enum_::enum_item(&type_).expect("internal error: invalid enum type");
+        let variant = enum_
+            .variant(&self.variant.slice)
+            .expect("internal error: variant not found");
+        let fn_name = enum_.transpiled_variant_fn_name(variant, ctx.index, &type_.generic_args);
+        let payload = self
+            .payload
+            .iter()
+            .map(|payload| payload.value.transpile(ctx, generic_args))
+            .join("");
+        format!("{fn_name}({payload})")
+    }
+}
+
+sequence!(
+    struct EnumVariantPayloadExpr {
+        start: OpenParenthesisSymbol,
+        #[force_error(true)]
+        value: MaybeBinaryExpr,
+        end: CloseParenthesisSymbol,
+    }
+);
+
+impl NodeConfig for EnumVariantPayloadExpr {}
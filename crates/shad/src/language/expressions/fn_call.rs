@@ -5,23 +5,32 @@ use crate::compilation::node::{
 };
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
+use crate::compilation::PRELUDE_PATH;
 use crate::language::expressions::binary::MaybeBinaryExpr;
 use crate::language::items::fn_;
 use crate::language::items::fn_::{FnItem, NativeFnItem};
+use crate::language::items::type_;
+use crate::language::items::type_::GenericParam;
 use crate::language::keywords::{
-    CloseParenthesisSymbol, ColonSymbol, CommaSymbol, OpenParenthesisSymbol,
+    CloseParenthesisSymbol, ColonSymbol, CommaSymbol, DoubleColonSymbol, OpenParenthesisSymbol,
 };
 use crate::language::patterns::Ident;
+use crate::language::type_ref;
+use crate::language::type_ref::TypeGenericArgs;
 use crate::language::{constants, sources};
 use crate::language::{transpilation, validations};
 use itertools::Itertools;
 use std::any::Any;
-use std::iter;
+use std::path::Path;
 use std::rc::Rc;
+use std::{iter, mem};
+
+const BARRIER_FN_NAMES: &[&str] = &["workgroup_barrier", "storage_barrier"];
 
 sequence!(
     struct FnCallExpr {
         ident: Ident,
+        generics: Repeated<FnCallGenericArgs, 0, 1>,
         args_start: OpenParenthesisSymbol,
         #[force_error(true)]
         args: Repeated<FnArgGroup, 0, 1>,
@@ -35,11 +44,16 @@ impl NodeConfig for FnCallExpr {
     }
 
     fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
-        let source = index.search(self, &self.source_key(index)?, sources::fn_criteria())?;
-        Some(NodeSource {
-            node: NodeRef::Other(source),
-            generic_args: vec![],
-        })
+        let arg_types = self
+            .args()
+            .map(|arg| arg.type_(index))
+            .collect::<Option<Vec<_>>>()?;
+        if let Some(generics) = self.generics.iter().next() {
+            let generic_args = generics.args.values(index);
+            explicit_fn_source(self, &self.ident.slice, generic_args, &arg_types, index)
+        } else {
+            fn_source(self, &self.ident.slice, arg_types, index)
+        }
     }
 
     fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
@@ -48,10 +62,20 @@ impl NodeConfig for FnCallExpr {
     }
 
     fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
-        self.source(index)?.node().type_(index)
+        fn_type(&self.source(index)?, index)
     }
 
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        if let Some(generics) = self.generics.iter().next() {
+            let key = sources::generic_fn_key(&self.ident.slice, self.args().count());
+            if let Some(fn_) = ctx.index.search(self, &key, sources::fn_criteria()) {
+                let error_count = ctx.errors.len();
+                type_ref::check_generic_arg_list(self, fn_, Some(&generics.args), ctx);
+                if ctx.errors.len() > error_count {
+                    return;
+                }
+            }
+        }
         validations::check_missing_source(self, ctx);
         if let Some(source) = self.source(ctx.index) {
             let arg_names = self
@@ -60,6 +84,8 @@ impl NodeConfig for FnCallExpr {
                 .flat_map(|args| args.args())
                 .map(|arg| arg.name.iter().map(|arg| &*arg.ident).next());
             check_arg_names(&source, arg_names, ctx);
+            check_generic_constraints(self, &source, ctx);
+            check_copied_args(&source, self.args(), ctx);
         }
     }
 
@@ -70,12 +96,9 @@ impl NodeConfig for FnCallExpr {
     }
 
     fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
-        let fn_ = self.source(ctx.index)?.node();
-        let args = constants::evaluate_fn_args(fn_, self.args(), ctx);
-        ctx.start_fn(args);
-        let value = fn_.evaluate_constant(ctx);
-        ctx.end_fn();
-        value
+        let fn_ = ctx.specialized(&self.source(ctx.index)?);
+        let args = constants::evaluate_fn_args(fn_.node(), self.args(), ctx)?;
+        ctx.evaluate_fn(&fn_, args)
     }
 
     fn transpile(
@@ -91,13 +114,31 @@ impl NodeConfig for FnCallExpr {
 }
 
 impl FnCallExpr {
-    fn args(&self) -> impl Iterator<Item = &MaybeBinaryExpr> {
+    // Only the prelude barrier functions are barriers.
+    pub(crate) fn is_barrier(&self, index: &NodeIndex) -> bool {
+        BARRIER_FN_NAMES.contains(&self.ident.slice.as_str())
+            && self
+                .source(index)
+                .is_some_and(|source| source.node().path == Path::new(PRELUDE_PATH))
+    }
+
+    pub(crate) fn args(&self) -> impl Iterator<Item = &MaybeBinaryExpr> {
         self.args
             .iter()
             .flat_map(|args| args.args().map(|arg| &*arg.expr))
     }
 }
 
+sequence!(
+    #[allow(unused_mut)]
+    struct FnCallGenericArgs {
+        separator: DoubleColonSymbol,
+        args: TypeGenericArgs,
+    }
+);
+
+impl NodeConfig for FnCallGenericArgs {}
+
 sequence!(
     struct FnArgGroup {
         first_arg: FnArg,
@@ -165,6 +206,181 @@ sequence!(
 
 impl NodeConfig for FnArgName {}
 
+/// Finds the function called with arguments of given types.
+///
+/// Non-generic functions are searched first. If not found, the generic function with the same
+/// name and parameter count is searched, and its generic arguments are inferred from the
+/// argument types. Finally, functions of interfaces constraining generic argument types are
+/// searched.
+pub(crate) fn fn_source<'a>(
+    node: &(impl Node + ?Sized),
+    name: &str,
+    arg_types: Vec<NodeSource<'a>>,
+    index: &'a NodeIndex,
+) -> Option<NodeSource<'a>> {
+    let key = sources::fn_key_from_operator(name, arg_types.iter().cloned(), index);
+    if let Some(fn_) = index.search(node, &key, sources::fn_criteria()) {
+        return Some(NodeSource {
+            node: NodeRef::Other(fn_),
+            generic_args: vec![],
+        });
+    }
+    let key = sources::generic_fn_key(name, arg_types.len());
+    if let Some(fn_) = index.search(node, &key, sources::fn_criteria()) {
+        if let Some(generic_args) = infer_generic_args(fn_, &arg_types, index) {
+            return Some(NodeSource {
+                node: NodeRef::Other(fn_),
+                generic_args,
+            });
+        }
+    }
+    interface_fn_source(name, &arg_types, index)
+}
+
+// Explicit generic arguments are only used to call generic functions.
+fn explicit_fn_source<'a>(
+    node: &(impl Node + ?Sized),
+    name: &str,
+    generic_args: GenericArgs<'a>,
+    arg_types: &[NodeSource<'a>],
+    index: &'a NodeIndex,
+) -> Option<NodeSource<'a>> {
+    let key = sources::generic_fn_key(name, arg_types.len());
+    let fn_ = index.search(node, &key, sources::fn_criteria())?;
+    if generic_args.len() != fn_.generic_params().len() || generic_args.iter().any(Option::is_none)
+    {
+        return None;
+    }
+    let is_matching = fn_::signature(fn_)
+        .params()
+        .zip(arg_types)
+        .all(|(param, arg_type)| {
+            !arg_type.is_no_return()
+                && param.type_(index).is_some_and(|param_type| {
+                    param_type
+                        .specialized(&generic_args, index)
+                        .are_same_types(arg_type, index)
+                        == Some(true)
+                })
+        });
+    is_matching.then_some(NodeSource {
+        node: NodeRef::Other(fn_),
+        generic_args,
+    })
+}
+
+fn interface_fn_source<'a>(
+    name: &str,
+    arg_types: &[NodeSource<'a>],
+    index: &'a NodeIndex,
+) -> Option<NodeSource<'a>> {
+    arg_types
+        .iter()
+        .filter_map(|type_| {
+            let param = (type_.type_item()? as &dyn Any).downcast_ref::<GenericParam>()?;
+            Some((type_, param.interface(index)?))
+        })
+        .flat_map(|(type_, interface)| interface.fns.iter().map(move |fn_| (type_, fn_)))
+        .find_map(|(type_, fn_)| {
+            let generic_args = vec![Some(type_.clone())];
+            let param_types = fn_
+                .signature
+                .params()
+                .map(|param| param.type_(index))
+                .collect::<Option<Vec<_>>>()?;
+            let is_matching = fn_.signature.ident.slice == name
+                && param_types.len() == arg_types.len()
+                && param_types
+                    .iter()
+                    .zip(arg_types)
+                    .all(|(param_type, arg_type)| {
+                        !arg_type.is_no_return()
+                            && param_type
+                                .specialized(&generic_args, index)
+                                .are_same_types(arg_type, index)
+                                == Some(true)
+                    });
+            is_matching.then(|| NodeSource {
+                node: NodeRef::Other(&**fn_),
+                generic_args,
+            })
+        })
+}
+
+pub(crate) fn fn_type<'a>(fn_: &NodeSource<'a>, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+    Some(
+        fn_.node()
+            .type_(index)?
+            .specialized(&fn_.generic_args, index),
+    )
+}
+
+fn infer_generic_args<'a>(
+    fn_: &'a dyn Node,
+    arg_types: &[NodeSource<'a>],
+    index: &'a NodeIndex,
+) -> Option<GenericArgs<'a>> {
+    let generic_params = fn_.generic_params();
+    let mut generic_args = vec![None; generic_params.len()];
+    let param_types = fn_::signature(fn_)
+        .params()
+        .map(|param| param.type_(index))
+        .collect::<Option<Vec<_>>>()?;
+    for (param_type, arg_type) in param_types.iter().zip(arg_types) {
+        if arg_type.is_no_return() {
+            return None;
+        }
+        infer_generic_args_from_type(param_type, arg_type, &generic_params, &mut generic_args);
+    }
+    if generic_args.iter().any(Option::is_none) {
+        return None;
+    }
+    for (param_type, arg_type) in param_types.iter().zip(arg_types) {
+        let param_type = param_type.specialized(&generic_args, index);
+        if param_type.are_same_types(arg_type, index) != Some(true) {
+            return None;
+        }
+    }
+    Some(generic_args)
+}
+
+fn infer_generic_args_from_type<'a>(
+    param_type: &NodeSource<'a>,
+    arg_type: &NodeSource<'a>,
+    generic_params: &[&GenericParam],
+    generic_args: &mut GenericArgs<'a>,
+) {
+    let generic_param_position = param_type.type_item().and_then(|param_type| {
+        generic_params
+            .iter()
+            .position(|generic_param| generic_param.id == param_type.id)
+    });
+    if let Some(position) = generic_param_position {
+        if generic_args[position].is_none() {
+            generic_args[position] = Some(arg_type.clone());
+        }
+    } else if arg_type.type_item().is_some() {
+        for (param_arg, arg_arg) in param_type.generic_args.iter().zip(&arg_type.generic_args) {
+            if let (Some(param_arg), Some(arg_arg)) = (param_arg, arg_arg) {
+                infer_generic_args_from_type(param_arg, arg_arg, generic_params, generic_args);
+            }
+        }
+    }
+}
+
+pub(crate) fn check_generic_constraints(
+    node: &dyn Node,
+    fn_: &NodeSource<'_>,
+    ctx: &mut ValidationContext<'_>,
+) {
+    let generic_params = fn_.node().generic_params();
+    for (param, arg) in generic_params.into_iter().zip(&fn_.generic_args) {
+        if let Some(arg) = arg {
+            validations::check_generic_constraint(node, param, arg, ctx);
+        }
+    }
+}
+
 pub(crate) fn check_arg_names<'a>(
     fn_: &NodeSource<'_>,
     arg_names: impl Iterator<Item = Option<&'a Ident>>,
@@ -175,24 +391,49 @@ pub(crate) fn check_arg_names<'a>(
     }
 }
 
+pub(crate) fn check_copied_args<'a>(
+    fn_: &NodeSource<'_>,
+    args: impl Iterator<Item = &'a impl Node>,
+    ctx: &mut ValidationContext<'_>,
+) {
+    for (arg, param) in args.zip(fn_::signature(fn_.node()).params()) {
+        if param.is_ref(ctx.index) != Some(true) {
+            validations::check_copyable_expr(arg, ctx);
+        }
+    }
+}
+
 pub(crate) fn transpile_fn_call<'a>(
     ctx: &mut TranspilationContext<'_>,
     fn_: &NodeSource<'_>,
     args: impl Iterator<Item = &'a impl Node>,
     generic_args: &GenericArgs<'_>,
 ) -> String {
+    let fn_ = fn_.specialized(generic_args, ctx.index);
     let node = fn_.node() as &dyn Any;
     if let Some(native_fn) = node.downcast_ref::<NativeFnItem>() {
-        let params = native_fn.signature.params().map(|p| &p.ident.slice);
-        let args = args.map(|arg| arg.transpile(ctx, generic_args));
+        let params = native_fn
+            .signature
+            .params()
+            .map(|p| &p.ident.slice)
+            .chain(native_fn.signature.generic_params().map(|p| &p.ident.slice));
+        let args = args
+            .map(|arg| arg.transpile(ctx, generic_args))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .chain(fn_.generic_args.iter().map(|arg| {
+                arg.as_ref()
+                    .expect("internal error: generic arg not found")
+                    .transpiled_type_name(ctx.index)
+            }));
         transpilation::resolve_placeholders(native_fn.transpilation.as_str(), params, args)
-    } else if let Some(fn_) = node.downcast_ref::<FnItem>() {
-        if fn_.is_inlined(ctx.index) {
-            transpile_inlined_fn_call(ctx, fn_, args, generic_args)
+    } else if let Some(fn_item) = node.downcast_ref::<FnItem>() {
+        if fn_item.is_inlined(ctx.index) {
+            transpile_inlined_fn_call(ctx, fn_item, args, generic_args, &fn_.generic_args)
         } else {
-            let fn_id = fn_.id;
+            let fn_name = type_::transpiled_name(fn_item.id, ctx.index, &fn_.generic_args);
             let args = args.map(|arg| arg.transpile(ctx, generic_args)).join(", ");
-            format!("_{fn_id}({args})")
+            format!("{fn_name}({args})")
         }
     } else {
         unreachable!("unknown function item")
@@ -204,9 +445,11 @@ pub(crate) fn transpile_inlined_fn_call<'a>(
     fn_: &FnItem,
     args: impl Iterator<Item = &'a impl Node>,
     generic_args: &GenericArgs<'_>,
+    fn_generic_args: &GenericArgs<'_>,
 ) -> String {
     let old_state = ctx.inline_state.clone();
     ctx.inline_state.is_inlined = true;
+    ctx.inline_state.returned_flag_id = None;
     ctx.start_block();
     let return_var_name = if fn_.is_ref(ctx.index) == Some(true) {
         ctx.inline_state.is_returning_ref = true;
@@ -214,7 +457,7 @@ pub(crate) fn transpile_inlined_fn_call<'a>(
     } else if let Some(return_type) = fn_.signature.return_type.iter().next() {
         let return_var_id = ctx.next_node_id();
         let return_var_name = format!("_{return_var_id}");
-        let return_type = return_type.type_.transpile(ctx, generic_args);
+        let return_type = return_type.type_.transpile(ctx, fn_generic_args);
         ctx.generated_stmts
             .push(format!("var {return_var_name}: {return_type};"));
         ctx.inline_state.return_var_id = Some(return_var_id);
@@ -235,8 +478,19 @@ pub(crate) fn transpile_inlined_fn_call<'a>(
             ctx.add_inline_mapping(param.id, var_name);
         }
     }
-    let inlined_stmts = fn_.body.transpile(ctx, &vec![]);
-    ctx.generated_stmts.push(inlined_stmts);
+    let call_stmts = mem::take(&mut ctx.generated_stmts);
+    let inlined_stmts = fn_.body.transpile(ctx, fn_generic_args);
+    ctx.generated_stmts = call_stmts;
+    if let Some(flag_id) = ctx.inline_state.returned_flag_id {
+        ctx.generated_stmts.push(format!("var _{flag_id} = false;"));
+    }
+    if return_var_name.is_some() {
+        // returned values are assigned to a variable followed by a `break`
+        ctx.generated_stmts
+            .push(format!("loop {{\n{inlined_stmts}\n}}"));
+    } else {
+        ctx.generated_stmts.push(inlined_stmts);
+    }
     ctx.end_block();
     let returned_ref = ctx.inline_state.returned_ref.take();
     ctx.inline_state = old_state;
@@ -3,4 +3,5 @@ pub(crate) mod chain;
 pub(crate) mod constructor;
 pub(crate) mod fn_call;
 pub(crate) mod simple;
+pub(crate) mod tuple;
 pub(crate) mod unary;
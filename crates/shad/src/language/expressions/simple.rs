@@ -1,26 +1,32 @@
 use crate::compilation::constant::{ConstantContext, ConstantData, ConstantValue};
 use crate::compilation::index::NodeIndex;
 use crate::compilation::node::{
-    choice, sequence, GenericArgs, Node, NodeConfig, NodeRef, NodeSource,
+    choice, sequence, transform, GenericArgs, Node, NodeConfig, NodeRef, NodeSource, Repeated,
 };
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
 use crate::compilation::PRELUDE_PATH;
 use crate::language::expressions::binary::MaybeBinaryExpr;
+use crate::language::expressions::tuple::{TupleExpr, TupleOtherItem};
+use crate::language::items::buffer::BufferItem;
+use crate::language::items::compute::InvocationIndex;
 use crate::language::items::constant::ConstantItem;
 use crate::language::items::fn_::FnParam;
 use crate::language::items::type_;
-use crate::language::items::type_::TypeItem;
+use crate::language::items::type_::{GenericParam, TypeItem};
 use crate::language::keywords::{
-    AlignofKeyword, CloseParenthesisSymbol, FalseKeyword, OpenParenthesisSymbol, SizeofKeyword,
-    TrueKeyword,
+    AlignofKeyword, CloseParenthesisSymbol, FalseKeyword, LenofKeyword, OpenParenthesisSymbol,
+    SizeofKeyword, TrueKeyword,
 };
 use crate::language::patterns::{Ident, U32Literal};
 use crate::language::sources;
-use crate::language::statements::{LocalRefDefStmt, LocalVarDefStmt};
+use crate::language::statements::{ForRange, LocalRefDefStmt, LocalTupleDefStmt, LocalVarDefStmt};
+use crate::language::transformations;
+use crate::language::type_ref;
 use crate::language::type_ref::Type;
 use crate::language::validations;
-use std::any::TypeId;
+use crate::ValidationError;
+use std::any::{Any, TypeId};
 use std::path::Path;
 
 sequence!(
@@ -101,7 +107,7 @@ impl NodeConfig for FalseLiteral {
     }
 }
 
-fn bool_type<'a>(node: &impl Node, index: &'a NodeIndex) -> &'a dyn TypeItem {
+pub(crate) fn bool_type<'a>(node: &impl Node, index: &'a NodeIndex) -> &'a dyn TypeItem {
     type_::to_item(
         index
             .search_in_path(
@@ -135,12 +141,21 @@ impl NodeConfig for VarIdentExpr {
     }
 
     fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
-        self.source(index)
-            .map(|source| source.node().node_type_id() != TypeId::of::<ConstantItem>())
+        self.source(index).map(|source| {
+            source.node().node_type_id() != TypeId::of::<ConstantItem>()
+                && source.node().node_type_id() != TypeId::of::<ForRange>()
+                && source.node().node_type_id() != TypeId::of::<InvocationIndex>()
+                && source.node().node_type_id() != TypeId::of::<GenericParam>()
+        })
     }
 
     fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
-        self.source(index)?.node().type_(index)
+        let source = self.source(index)?.node();
+        if let Some(tuple_def) = (source as &dyn Any).downcast_ref::<LocalTupleDefStmt>() {
+            tuple_def.var_type(&self.ident.slice, index)
+        } else {
+            source.type_(index)
+        }
     }
 
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
@@ -151,8 +166,11 @@ impl NodeConfig for VarIdentExpr {
         let source = self.source(index)?.node();
         if source.node_type_id() == TypeId::of::<ConstantItem>()
             || source.node_type_id() == TypeId::of::<LocalVarDefStmt>()
+            || source.node_type_id() == TypeId::of::<LocalTupleDefStmt>()
             || source.node_type_id() == TypeId::of::<LocalRefDefStmt>()
+            || source.node_type_id() == TypeId::of::<ForRange>()
             || source.node_type_id() == TypeId::of::<FnParam>()
+            || source.node_type_id() == TypeId::of::<GenericParam>()
         {
             None
         } else {
@@ -164,6 +182,18 @@ impl NodeConfig for VarIdentExpr {
         let var_def = self.source(ctx.index)?.node();
         if var_def.node_type_id() == TypeId::of::<ConstantItem>() {
             var_def.evaluate_constant(ctx)
+        } else if let Some(param) = (var_def as &dyn Any).downcast_ref::<GenericParam>() {
+            ctx.generic_value(param.position(ctx.index)?).cloned()
+        } else if let Some(tuple_def) = (var_def as &dyn Any).downcast_ref::<LocalTupleDefStmt>() {
+            let position = tuple_def.var_position(&self.ident.slice)?;
+            match &ctx.var_value(var_def.id)?.data {
+                ConstantData::StructFields(fields) => Some(fields.get(position)?.value.clone()),
+                ConstantData::F32(_)
+                | ConstantData::I32(_)
+                | ConstantData::U32(_)
+                | ConstantData::Bool(_)
+                | ConstantData::Array(_) => unreachable!("const tuple variable is not a tuple"),
+            }
         } else {
             ctx.var_value(var_def.id).cloned()
         }
@@ -172,31 +202,73 @@ impl NodeConfig for VarIdentExpr {
     fn transpile(
         &self,
         ctx: &mut TranspilationContext<'_>,
-        _generic_args: &GenericArgs<'_>,
+        generic_args: &GenericArgs<'_>,
     ) -> String {
-        let source_id = self
+        let source = self
             .source(ctx.index)
             .expect("internal error: var ident source not found")
-            .node()
-            .id;
-        if let Some(mapping) = ctx.inline_mapping(source_id) {
+            .node();
+        if let Some(param) = (source as &dyn Any).downcast_ref::<GenericParam>() {
+            let param = NodeSource {
+                node: NodeRef::Type(param),
+                generic_args: vec![],
+            };
+            return param
+                .specialized(generic_args, ctx.index)
+                .transpiled_type_name(ctx.index);
+        }
+        let source_id = source.id;
+        let var_name = if let Some(mapping) = ctx.inline_mapping(source_id) {
             mapping.to_string()
         } else {
             format!("_{source_id}")
+        };
+        if let Some(tuple_def) = (source as &dyn Any).downcast_ref::<LocalTupleDefStmt>() {
+            let position = tuple_def
+                .var_position(&self.ident.slice)
+                .expect("internal error: tuple variable not found");
+            format!("{var_name}._{position}")
+        } else {
+            var_name
+        }
+    }
+}
+
+transform!(
+    ParenthesizedExpr,
+    ParsedParenthesizedExpr,
+    TupleExpr,
+    transformations::transform_parenthesized_expr
+);
+
+impl ParenthesizedExpr {
+    pub(crate) fn as_var_ident(&self) -> Option<&VarIdentExpr> {
+        match self {
+            Self::Parsed(expr) => expr.expr.as_var_ident(),
+            Self::Transformed(_) => None,
+        }
+    }
+
+    pub(crate) fn as_lenof(&self) -> Option<&LenofExpr> {
+        match self {
+            Self::Parsed(expr) => expr.expr.as_lenof(),
+            Self::Transformed(_) => None,
         }
     }
 }
 
+// A parenthesized expression containing several comma-separated items is transformed into a tuple.
 sequence!(
-    struct ParenthesizedExpr {
+    struct ParsedParenthesizedExpr {
         open: OpenParenthesisSymbol,
         #[force_error(true)]
         expr: MaybeBinaryExpr,
+        other_items: Repeated<TupleOtherItem, 0, { usize::MAX }>,
         close: CloseParenthesisSymbol,
     }
 );
 
-impl NodeConfig for ParenthesizedExpr {
+impl NodeConfig for ParsedParenthesizedExpr {
     fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
         self.expr.source(index)
     }
@@ -209,6 +281,10 @@ impl NodeConfig for ParenthesizedExpr {
         self.expr.type_(index)
     }
 
+    fn validate(&self, _ctx: &mut ValidationContext<'_>) {
+        debug_assert!(self.other_items.iter().len() == 0);
+    }
+
     fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
         self.expr.invalid_constant(index)
     }
@@ -249,37 +325,136 @@ impl NodeConfig for TypeOperationExpr {
         })
     }
 
-    fn invalid_constant(&self, _index: &NodeIndex) -> Option<&dyn Node> {
-        None
+    // The value of generic types is only known once the generic arguments are known.
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.type_
+            .type_(index)
+            .is_some_and(|type_| type_.is_generic())
+            .then_some(self)
     }
 
     fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        let type_ = self.type_.type_(ctx.index)?;
         Some(ConstantValue {
             transpiled_type_name: "u32".to_string(),
-            data: ConstantData::U32(self.value(ctx.index)?),
+            data: ConstantData::U32(self.value(&type_, ctx.index)?),
         })
     }
 
     fn transpile(
         &self,
         ctx: &mut TranspilationContext<'_>,
-        _generic_args: &GenericArgs<'_>,
+        generic_args: &GenericArgs<'_>,
     ) -> String {
+        let type_ = self
+            .type_
+            .type_(ctx.index)
+            .expect("internal error: type not found")
+            .specialized(generic_args, ctx.index);
         let value = self
-            .value(ctx.index)
+            .value(&type_, ctx.index)
             .expect("internal error: cannot run type operation on an invalid type");
         format!("{value}u")
     }
 }
 
 impl TypeOperationExpr {
-    fn value(&self, index: &NodeIndex) -> Option<u32> {
-        let type_item = self.type_.item(index)?;
+    fn value(&self, type_: &NodeSource<'_>, index: &NodeIndex) -> Option<u32> {
+        if type_.is_generic() {
+            return None;
+        }
+        let type_item = type_.type_item()?;
         Some(match *self.operator {
-            TypeOperator::Alignof(_) => type_item.alignment(index),
-            TypeOperator::Sizeof(_) => type_item.size(index),
+            TypeOperator::Alignof(_) => type_item.alignment(index, &type_.generic_args),
+            TypeOperator::Sizeof(_) => type_item.size(index, &type_.generic_args),
+        })
+    }
+}
+
+sequence!(
+    struct LenofExpr {
+        operator: LenofKeyword,
+        #[force_error(true)]
+        start: OpenParenthesisSymbol,
+        expr: MaybeBinaryExpr,
+        end: CloseParenthesisSymbol,
+    }
+);
+
+impl NodeConfig for LenofExpr {
+    fn is_ref(&self, _index: &NodeIndex) -> Option<bool> {
+        Some(false)
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        Some(NodeSource {
+            node: NodeRef::Type(U32Literal::u32_type(self, index)),
+            generic_args: vec![],
+        })
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        if let Some(type_) = self.expr.type_(ctx.index) {
+            if !type_.is_no_return() && !type_ref::is_array(&type_) {
+                let type_name = type_.name_or_no_return(ctx.index);
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    &*self.expr,
+                    "invalid expression type",
+                    Some(&format!(
+                        "expression type is `{type_name}` but expected type is an array"
+                    )),
+                    &[],
+                ));
+            }
+        }
+        validations::check_no_return_type(&*self.expr, ctx);
+    }
+
+    // The expression is never evaluated for fixed-size arrays, as the length is known statically.
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.expr
+            .type_(index)
+            .is_some_and(|type_| type_ref::is_runtime_array(&type_) || type_.is_generic())
+            .then_some(self)
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        Some(ConstantValue {
+            transpiled_type_name: "u32".to_string(),
+            data: ConstantData::U32(self.length(ctx.index)?),
         })
     }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let type_ = self
+            .expr
+            .type_(ctx.index)
+            .expect("internal error: expression type not found")
+            .specialized(generic_args, ctx.index);
+        if let Some(length) = type_ref::array_length(&type_) {
+            format!("{length}u")
+        } else {
+            let expr = self.expr.transpile(ctx, generic_args);
+            format!("arrayLength(&{expr})")
+        }
+    }
+}
+
+impl LenofExpr {
+    pub(crate) fn buffer<'a>(&'a self, index: &'a NodeIndex) -> Option<&'a BufferItem> {
+        let source = self.expr.as_var_ident()?.source(index)?.node();
+        let buffer = (source as &dyn Any).downcast_ref::<BufferItem>()?;
+        type_ref::is_runtime_array(&buffer.type_(index)?).then_some(buffer)
+    }
+
+    fn length(&self, index: &NodeIndex) -> Option<u32> {
+        type_ref::array_length(&self.expr.type_(index)?)
+    }
 }
 
 choice!(
@@ -0,0 +1,149 @@
+use crate::compilation::constant::{
+    ConstantContext, ConstantData, ConstantStructFieldData, ConstantValue,
+};
+use crate::compilation::index::NodeIndex;
+use crate::compilation::node::{
+    sequence, GenericArgs, Node, NodeConfig, NodeRef, NodeSource, Repeated,
+};
+use crate::compilation::transpilation::TranspilationContext;
+use crate::compilation::validation::ValidationContext;
+use crate::language::expressions::binary::MaybeBinaryExpr;
+use crate::language::items::type_::{StructField, TypeItem};
+use crate::language::keywords::{CloseParenthesisSymbol, CommaSymbol, OpenParenthesisSymbol};
+use crate::language::type_ref;
+use crate::language::validations;
+use itertools::Itertools;
+use std::iter;
+
+// A tuple expression is also the type item of its own value, as no tuple type is declared.
+sequence!(
+    struct TupleExpr {
+        start: OpenParenthesisSymbol,
+        #[force_error(true)]
+        first_item: MaybeBinaryExpr,
+        other_items: Repeated<TupleOtherItem, 0, { usize::MAX }>,
+        end: CloseParenthesisSymbol,
+    }
+);
+
+impl NodeConfig for TupleExpr {
+    fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.type_(index)
+    }
+
+    fn is_ref(&self, _index: &NodeIndex) -> Option<bool> {
+        Some(false)
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        Some(NodeSource {
+            node: NodeRef::Type(self),
+            generic_args: self.items().map(|item| item.type_(index)).collect(),
+        })
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        for item in self.items() {
+            validations::check_no_return_type(item, ctx);
+            validations::check_copyable_expr(item, ctx);
+        }
+    }
+
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.items().find_map(|item| item.invalid_constant(index))
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        let type_ = ctx.specialized(&self.type_(ctx.index)?);
+        Some(ConstantValue {
+            transpiled_type_name: type_.transpiled_type_name(ctx.index),
+            data: ConstantData::StructFields(
+                self.items()
+                    .enumerate()
+                    .map(|(position, item)| {
+                        Some(ConstantStructFieldData {
+                            name: position.to_string(),
+                            value: item.evaluate_constant(ctx)?,
+                            is_alias: false,
+                        })
+                    })
+                    .collect::<Option<_>>()?,
+            ),
+        })
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let type_name = self
+            .type_(ctx.index)
+            .expect("internal error: tuple type not found")
+            .specialized(generic_args, ctx.index)
+            .transpiled_type_name(ctx.index);
+        let items = self
+            .items()
+            .map(|item| item.transpile(ctx, generic_args))
+            .join(", ");
+        format!("{type_name}({items})")
+    }
+}
+
+impl TypeItem for TupleExpr {
+    fn is_native(&self) -> bool {
+        false
+    }
+
+    fn name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        type_ref::tuple_name(index, generic_args)
+    }
+
+    fn is_same_item(&self, other: &dyn TypeItem, _index: &NodeIndex) -> Option<bool> {
+        Some(type_ref::tuple_item_count(other) == Some(self.items().count()))
+    }
+
+    fn fields(&self) -> Vec<&StructField> {
+        vec![]
+    }
+
+    fn field(&self, _field_name: &str) -> Option<&StructField> {
+        None
+    }
+
+    fn size(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        type_ref::tuple_size(index, generic_args)
+    }
+
+    fn alignment(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        type_ref::tuple_alignment(index, generic_args)
+    }
+
+    fn transpiled_name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        type_ref::tuple_transpiled_name(index, generic_args)
+    }
+
+    fn transpiled_field_name(&self, field_name: &str) -> String {
+        format!("_{field_name}")
+    }
+}
+
+impl TupleExpr {
+    pub(crate) fn items(&self) -> impl Iterator<Item = &MaybeBinaryExpr> {
+        iter::once(&*self.first_item).chain(self.other_items.iter().map(|other| &*other.item))
+    }
+}
+
+sequence!(
+    struct TupleOtherItem {
+        comma: CommaSymbol,
+        #[force_error(true)]
+        item: MaybeBinaryExpr,
+    }
+);
+
+impl NodeConfig for TupleOtherItem {}
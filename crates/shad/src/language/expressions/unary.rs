@@ -1,14 +1,13 @@
 use crate::compilation::constant::{ConstantContext, ConstantValue};
 use crate::compilation::index::NodeIndex;
-use crate::compilation::node::{
-    choice, sequence, GenericArgs, Node, NodeConfig, NodeRef, NodeSource,
-};
+use crate::compilation::node::{choice, sequence, GenericArgs, Node, NodeConfig, NodeSource};
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
 use crate::language::expressions::chain::ChainExpr;
+use crate::language::expressions::fn_call;
 use crate::language::expressions::fn_call::transpile_fn_call;
 use crate::language::items::fn_;
-use crate::language::keywords::{ExclamationSymbol, HyphenSymbol};
+use crate::language::keywords::{ExclamationSymbol, HyphenSymbol, TildeSymbol};
 use crate::language::validations;
 use crate::language::{constants, sources};
 use std::iter;
@@ -23,20 +22,21 @@ sequence!(
 
 impl NodeConfig for UnaryExpr {
     fn source_key(&self, index: &NodeIndex) -> Option<String> {
-        let fn_name = match &*self.operator {
-            UnaryOperator::Sub(_) => "__neg__",
-            UnaryOperator::Not(_) => "__not__",
-        };
         let arg_type = self.operand.type_(index)?;
-        Some(sources::fn_key_from_operator(fn_name, [arg_type]))
+        Some(sources::fn_key_from_operator(
+            self.fn_name(),
+            [arg_type],
+            index,
+        ))
     }
 
     fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
-        let source = index.search(self, &self.source_key(index)?, sources::fn_criteria())?;
-        Some(NodeSource {
-            node: NodeRef::Other(source),
-            generic_args: vec![],
-        })
+        fn_call::fn_source(
+            self,
+            self.fn_name(),
+            vec![self.operand.type_(index)?],
+            index,
+        )
     }
 
     fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
@@ -45,11 +45,15 @@ impl NodeConfig for UnaryExpr {
     }
 
     fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
-        self.source(index)?.node().type_(index)
+        fn_call::fn_type(&self.source(index)?, index)
     }
 
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
         validations::check_missing_source(self, ctx);
+        if let Some(source) = self.source(ctx.index) {
+            fn_call::check_generic_constraints(self, &source, ctx);
+            fn_call::check_copied_args(&source, iter::once(&*self.operand), ctx);
+        }
     }
 
     fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
@@ -59,12 +63,9 @@ impl NodeConfig for UnaryExpr {
     }
 
     fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
-        let fn_ = self.source(ctx.index)?.node();
-        let args = constants::evaluate_fn_args(fn_, iter::once(&*self.operand), ctx);
-        ctx.start_fn(args);
-        let value = fn_.evaluate_constant(ctx);
-        ctx.end_fn();
-        value
+        let fn_ = ctx.specialized(&self.source(ctx.index)?);
+        let args = constants::evaluate_fn_args(fn_.node(), iter::once(&*self.operand), ctx)?;
+        ctx.evaluate_fn(&fn_, args)
     }
 
     fn transpile(
@@ -79,9 +80,20 @@ impl NodeConfig for UnaryExpr {
     }
 }
 
+impl UnaryExpr {
+    fn fn_name(&self) -> &'static str {
+        match &*self.operator {
+            UnaryOperator::Sub(_) => "__neg__",
+            UnaryOperator::Not(_) => "__not__",
+            UnaryOperator::BitNot(_) => "__bnot__",
+        }
+    }
+}
+
 choice!(
     enum UnaryOperator {
         Sub(HyphenSymbol),
         Not(ExclamationSymbol),
+        BitNot(TildeSymbol),
     }
 );
@@ -4,10 +4,11 @@ use crate::compilation::node::{sequence, GenericArgs, Node, NodeConfig, Repeated
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
 use crate::language::keywords::{CloseCurlyBracketSymbol, OpenCurlyBracketSymbol};
-use crate::language::statements::Stmt;
+use crate::language::statements::{ExprStmt, ReturnStmt, SharedDefStmt, Stmt};
+use crate::language::validations;
 use crate::ValidationError;
 use itertools::Itertools;
-use std::mem;
+use std::{iter, mem};
 
 sequence!(
     #[allow(unused_mut)]
@@ -18,12 +19,7 @@ sequence!(
 
 impl NodeConfig for NonReturnBlock {
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
-        if let Some(return_stmt) = self
-            .inner
-            .statements
-            .iter()
-            .find_map(|stmt| stmt.as_return())
-        {
+        for return_stmt in self.inner.return_stmts() {
             ctx.errors.push(ValidationError::error(
                 ctx,
                 return_stmt,
@@ -32,6 +28,25 @@ impl NodeConfig for NonReturnBlock {
                 &[],
             ));
         }
+        validations::check_loop_control_stmts(&self.inner, ctx);
+        for shared_def in self.inner.nested_shared_def_stmts() {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                shared_def,
+                "`shared` variable defined in a nested block",
+                Some("only allowed at the top level of a `run` or `init` block"),
+                &[],
+            ));
+        }
+        for barrier in self.inner.non_uniform_barrier_stmts(ctx.index) {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                barrier,
+                "barrier in non-uniform control flow",
+                Some("barrier possibly not reached by all invocations"),
+                &[],
+            ));
+        }
     }
 
     fn transpile(
@@ -77,10 +92,13 @@ impl NodeConfig for Block {
     }
 
     fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
-        self.statements
-            .iter()
-            .filter_map(|stmt| stmt.evaluate_constant(ctx))
-            .last()
+        for stmt in self.statements.iter() {
+            let value = stmt.evaluate_constant(ctx);
+            if value.is_some() || ctx.is_interrupted() {
+                return value;
+            }
+        }
+        None
     }
 
     fn transpile(
@@ -108,4 +126,74 @@ impl Block {
     pub(crate) fn last_stmt(&self) -> Option<&Stmt> {
         self.statements.iter().last().map(|stmt| &**stmt)
     }
+
+    pub(crate) fn is_returning(&self) -> bool {
+        self.last_stmt().is_some_and(Stmt::is_returning)
+    }
+
+    pub(crate) fn return_stmts(&self) -> Vec<&ReturnStmt> {
+        self.statements
+            .iter()
+            .flat_map(|stmt| stmt.return_stmts())
+            .collect()
+    }
+
+    pub(crate) fn loop_control_stmts(&self) -> Vec<&Stmt> {
+        self.statements
+            .iter()
+            .flat_map(|stmt| stmt.loop_control_stmts())
+            .collect()
+    }
+
+    // Statements of the block, including the ones in nested blocks.
+    pub(crate) fn nested_stmts(&self) -> Vec<&Stmt> {
+        self.statements
+            .iter()
+            .flat_map(|stmt| {
+                iter::once(&**stmt).chain(
+                    stmt.nested_blocks()
+                        .into_iter()
+                        .flat_map(Self::nested_stmts),
+                )
+            })
+            .collect()
+    }
+
+    pub(crate) fn shared_def_stmts(&self) -> impl Iterator<Item = &SharedDefStmt> {
+        self.statements
+            .iter()
+            .filter_map(|stmt| stmt.as_shared_def())
+    }
+
+    pub(crate) fn nested_shared_def_stmts(&self) -> Vec<&SharedDefStmt> {
+        self.statements
+            .iter()
+            .flat_map(|stmt| stmt.nested_blocks())
+            .flat_map(Self::nested_stmts)
+            .filter_map(Stmt::as_shared_def)
+            .collect()
+    }
+
+    pub(crate) fn barrier_stmts(&self, index: &NodeIndex) -> Vec<&ExprStmt> {
+        self.nested_stmts()
+            .into_iter()
+            .filter_map(Stmt::as_expr)
+            .filter(|stmt| stmt.is_barrier(index))
+            .collect()
+    }
+
+    // Control flow is considered uniform only outside nested blocks and in uniform `for` loops.
+    pub(crate) fn non_uniform_barrier_stmts(&self, index: &NodeIndex) -> Vec<&ExprStmt> {
+        self.statements
+            .iter()
+            .flat_map(|stmt| match stmt.as_for() {
+                Some(for_) if for_.is_uniform(index) => for_.block.non_uniform_barrier_stmts(index),
+                _ => stmt
+                    .nested_blocks()
+                    .into_iter()
+                    .flat_map(|block| block.barrier_stmts(index))
+                    .collect(),
+            })
+            .collect()
+    }
 }
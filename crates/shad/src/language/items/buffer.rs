@@ -1,12 +1,14 @@
 use crate::compilation::index::NodeIndex;
-use crate::compilation::node::{sequence, GenericArgs, NodeConfig, NodeSource, Repeated};
+use crate::compilation::node::{choice, sequence, GenericArgs, NodeConfig, NodeSource, Repeated};
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
+use crate::compilation::PRELUDE_PATH;
 use crate::language::expressions::binary::MaybeBinaryExpr;
 use crate::language::items::type_::TypeItem;
 use crate::language::items::{is_item_recursive, transpiled_dependencies};
-use crate::language::keywords::{BufKeyword, EqSymbol, PubKeyword, SemicolonSymbol};
+use crate::language::keywords::{BufKeyword, ColonSymbol, EqSymbol, PubKeyword, SemicolonSymbol};
 use crate::language::patterns::Ident;
+use crate::language::type_ref::{NamedType, RuntimeArrayType};
 use crate::language::{sources, validations};
 use indoc::indoc;
 use itertools::Itertools;
@@ -18,8 +20,7 @@ sequence!(
         buf: BufKeyword,
         #[force_error(true)]
         ident: Ident,
-        eq: EqSymbol,
-        expr: MaybeBinaryExpr,
+        value: BufferValue,
         semicolon: SemicolonSymbol,
     }
 );
@@ -37,14 +38,13 @@ impl NodeConfig for BufferItem {
         if is_item_recursive(self, index) {
             None
         } else {
-            self.expr.type_(index)
+            self.value.type_(index)
         }
     }
 
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
         validations::check_duplicated_items(self, ctx);
         validations::check_recursive_items(self, ctx);
-        validations::check_no_return_type(&*self.expr, ctx);
     }
 
     fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
@@ -79,8 +79,15 @@ impl BufferItem {
             .expect("internal error: invalid buffer type")
     }
 
+    pub(crate) fn expr(&self) -> Option<&MaybeBinaryExpr> {
+        self.value.as_expr().map(|value| &*value.expr)
+    }
+
     pub(crate) fn transpile_shader(&self, ctx: &mut TranspilationContext<'_>) -> String {
-        let expr = self.expr.transpile(ctx, &vec![]);
+        let expr = self
+            .expr()
+            .expect("internal error: buffer without initial value")
+            .transpile(ctx, &vec![]);
         format!(
             indoc!(
                 "{dependencies}
@@ -102,11 +109,17 @@ impl BufferItem {
     }
 
     pub(crate) fn item_path(&self, root_path: &Path) -> String {
-        format!(
-            "{}.{}",
+        // the prelude is not located in the root folder
+        let relative_path = if self.path == Path::new(PRELUDE_PATH) {
+            &self.path
+        } else {
             self.path
                 .strip_prefix(root_path)
                 .expect("internal error: invalid root path")
+        };
+        format!(
+            "{}.{}",
+            relative_path
                 .with_extension("")
                 .components()
                 .map(|component| component.as_os_str().to_string_lossy())
@@ -115,3 +128,53 @@ impl BufferItem {
         )
     }
 }
+
+choice!(
+    enum BufferValue {
+        Expr(BufferExpr),
+        Type(BufferType),
+    }
+);
+
+sequence!(
+    #[allow(unused_mut)]
+    struct BufferExpr {
+        eq: EqSymbol,
+        #[force_error(true)]
+        expr: MaybeBinaryExpr,
+    }
+);
+
+impl NodeConfig for BufferExpr {
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.expr.type_(index)
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        validations::check_no_return_type(&*self.expr, ctx);
+        validations::check_copyable_expr(&*self.expr, ctx);
+    }
+}
+
+sequence!(
+    #[allow(unused_mut)]
+    struct BufferType {
+        colon: ColonSymbol,
+        #[force_error(true)]
+        type_: BufferTypeRef,
+    }
+);
+
+impl NodeConfig for BufferType {
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.type_.type_(index)
+    }
+}
+
+// Buffers defined with a type are zero-initialized.
+choice!(
+    enum BufferTypeRef {
+        RuntimeArray(RuntimeArrayType),
+        Named(NamedType),
+    }
+);
@@ -1,43 +1,57 @@
+use crate::compilation::constant::{ConstantContext, ConstantData, ConstantValue};
 use crate::compilation::index::NodeIndex;
-use crate::compilation::node::{sequence, NodeConfig, NodeRef, NodeSource, Repeated};
-use crate::compilation::transpilation::TranspilationContext;
+use crate::compilation::node::{
+    choice, sequence, GenericArgs, Node, NodeConfig, NodeRef, NodeSource, Repeated,
+};
+use crate::compilation::transpilation::{InvocationCount, TranspilationContext};
 use crate::compilation::validation::ValidationContext;
-use crate::language::expressions::binary::MaybeBinaryExpr;
+use crate::language::expressions::binary::{BlockHeaderExpr, MaybeBinaryExpr};
+use crate::language::expressions::simple;
 use crate::language::items::block::NonReturnBlock;
+use crate::language::items::buffer::BufferItem;
 use crate::language::items::transpiled_dependencies;
 use crate::language::keywords::{
-    CloseParenthesisSymbol, InitKeyword, OpenParenthesisSymbol, PrioKeyword, RunKeyword,
+    CloseParenthesisSymbol, CloseSquareBracketSymbol, EveryKeyword, IfKeyword, IndirectKeyword,
+    InitKeyword, OpenParenthesisSymbol, OpenSquareBracketSymbol, PipeSymbol, PrioKeyword,
+    RunKeyword,
 };
-use crate::language::patterns::I32Literal;
-use crate::language::validations;
+use crate::language::patterns::{I32Literal, Ident, U32Literal};
+use crate::language::{races, sources, type_ref, validations};
+use crate::ValidationError;
 use indoc::indoc;
+use itertools::Itertools;
+use std::any::Any;
+use std::mem;
+
+/// The number of invocations run by a workgroup of a shader with a dispatch.
+///
+/// It must match the `WORKGROUP_SIZE` constant of the prelude.
+pub(crate) const WORKGROUP_SIZE: u32 = 64;
 
 sequence!(
     struct InitItem {
         init: InitKeyword,
         #[force_error(true)]
+        dispatch: Repeated<Dispatch, 0, 1>,
         priority: Repeated<Priority, 0, 1>,
         block: NonReturnBlock,
     }
 );
 
-impl NodeConfig for InitItem {}
+impl NodeConfig for InitItem {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        check_dispatch_uniformity(self.dispatch(), &self.block, ctx);
+        races::check_data_races(self.dispatch(), &self.block.inner, ctx);
+    }
+}
 
 impl InitItem {
     pub(crate) fn transpile_shader(&self, ctx: &mut TranspilationContext<'_>) -> String {
-        format!(
-            indoc!(
-                "{dependencies}
+        transpile_shader(self, self.dispatch(), &self.block, ctx)
+    }
 
-                @compute
-                @workgroup_size(1, 1, 1)
-                fn main() {{
-                {block}
-                }}"
-            ),
-            dependencies = transpiled_dependencies(ctx, self),
-            block = self.block.transpile(ctx, &vec![]),
-        )
+    pub(crate) fn dispatch(&self) -> Option<&Dispatch> {
+        self.dispatch.iter().next().map(|dispatch| &**dispatch)
     }
 
     pub(crate) fn priority(&self, index: &NodeIndex) -> i32 {
@@ -53,37 +67,159 @@ sequence!(
     struct RunItem {
         run: RunKeyword,
         #[force_error(true)]
+        dispatch: Repeated<Dispatch, 0, 1>,
         priority: Repeated<Priority, 0, 1>,
+        frequency: Repeated<Frequency, 0, 1>,
+        condition: Repeated<RunCondition, 0, 1>,
         block: NonReturnBlock,
     }
 );
 
-impl NodeConfig for RunItem {}
+impl NodeConfig for RunItem {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        check_dispatch_uniformity(self.dispatch(), &self.block, ctx);
+        races::check_data_races(self.dispatch(), &self.block.inner, ctx);
+        self.check_condition_invocation_index(ctx);
+    }
+}
 
 impl RunItem {
     pub(crate) fn transpile_shader(&self, ctx: &mut TranspilationContext<'_>) -> String {
+        transpile_shader(self, self.dispatch(), &self.block, ctx)
+    }
+
+    pub(crate) fn dispatch(&self) -> Option<&Dispatch> {
+        self.dispatch.iter().next().map(|dispatch| &**dispatch)
+    }
+
+    pub(crate) fn priority(&self, index: &NodeIndex) -> i32 {
+        self.priority
+            .iter()
+            .map(|priority| priority.value.parse_const_i32(index))
+            .next()
+            .unwrap_or(0)
+    }
+
+    pub(crate) fn frequency(&self, index: &NodeIndex) -> u32 {
+        self.frequency
+            .iter()
+            .map(|frequency| {
+                frequency
+                    .value
+                    .parse_const_u32(&mut ConstantContext::new(index))
+            })
+            .next()
+            .unwrap_or(1)
+    }
+
+    pub(crate) fn condition(&self) -> Option<&RunCondition> {
+        self.condition.iter().next().map(|condition| &**condition)
+    }
+
+    // The condition is evaluated once for all invocations.
+    fn check_condition_invocation_index(&self, ctx: &mut ValidationContext<'_>) {
+        let Some(condition) = self.condition() else {
+            return;
+        };
+        let Some(invocation_index) = self.dispatch().and_then(Dispatch::invocation_index) else {
+            return;
+        };
+        let is_index_used = condition
+            .direct_nested_sources(ctx.index)
+            .iter()
+            .any(|source| source.node().id == invocation_index.id);
+        if is_index_used {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*condition.condition,
+                "invalid run condition",
+                Some("invocation index cannot be used in a run condition"),
+                &[(invocation_index, "invocation index defined here")],
+            ));
+        }
+    }
+}
+
+sequence!(
+    struct Frequency {
+        every: EveryKeyword,
+        #[force_error(true)]
+        args_start: OpenParenthesisSymbol,
+        value: MaybeBinaryExpr,
+        args_end: CloseParenthesisSymbol,
+    }
+);
+
+impl NodeConfig for Frequency {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        let u32_type = NodeSource {
+            node: NodeRef::Type(U32Literal::u32_type(self, ctx.index)),
+            generic_args: vec![],
+        };
+        validations::check_invalid_const_expr_type(&u32_type, &*self.value, ctx);
+        validations::check_invalid_const_scope(&*self.value, &*self.every, ctx);
+        if let Some(ConstantValue {
+            data: ConstantData::U32(0),
+            ..
+        }) = self
+            .value
+            .evaluate_constant(&mut ConstantContext::new(ctx.index))
+        {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.value,
+                "invalid run frequency",
+                Some("frequency should be greater than zero"),
+                &[],
+            ));
+        }
+    }
+}
+
+sequence!(
+    struct RunCondition {
+        if_: IfKeyword,
+        #[force_error(true)]
+        condition: BlockHeaderExpr,
+    }
+);
+
+impl NodeConfig for RunCondition {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        let bool_type = NodeSource {
+            node: NodeRef::Type(simple::bool_type(self, ctx.index)),
+            generic_args: vec![],
+        };
+        validations::check_invalid_const_expr_type(&bool_type, &*self.condition, ctx);
+    }
+}
+
+impl RunCondition {
+    // The condition is evaluated by a separate shader before each run, which sets the last
+    // workgroup count of the indirect dispatch arguments to 0 or 1.
+    pub(crate) fn transpile_shader(&self, ctx: &mut TranspilationContext<'_>) -> String {
+        let dependencies = transpiled_dependencies(ctx, &*self.condition);
+        let condition = self.condition.transpile(ctx, &vec![]);
         format!(
             indoc!(
                 "{dependencies}
 
+                @group(0) @binding({binding})
+                var<storage, read_write> dispatch_args: array<u32, 3>;
+
                 @compute
                 @workgroup_size(1, 1, 1)
                 fn main() {{
-                {block}
+                {stmts}
+                dispatch_args[2] = u32(bool({condition}));
                 }}"
             ),
-            dependencies = transpiled_dependencies(ctx, self),
-            block = self.block.transpile(ctx, &vec![]),
+            dependencies = dependencies,
+            binding = ctx.next_binding(),
+            stmts = mem::take(&mut ctx.generated_stmts).join("\n"),
+            condition = condition,
         )
     }
-
-    pub(crate) fn priority(&self, index: &NodeIndex) -> i32 {
-        self.priority
-            .iter()
-            .map(|priority| priority.value.parse_const_i32(index))
-            .next()
-            .unwrap_or(0)
-    }
 }
 
 sequence!(
@@ -106,3 +242,327 @@ impl NodeConfig for Priority {
         validations::check_invalid_const_scope(&*self.value, &*self.prio, ctx);
     }
 }
+
+sequence!(
+    struct Dispatch {
+        start: OpenSquareBracketSymbol,
+        #[force_error(true)]
+        count: DispatchCount,
+        end: CloseSquareBracketSymbol,
+        index: Repeated<InvocationIndexGroup, 0, 1>,
+    }
+);
+
+impl NodeConfig for Dispatch {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        let Some(count) = self.count.as_direct() else {
+            return;
+        };
+        let u32_type = NodeSource {
+            node: NodeRef::Type(U32Literal::u32_type(self, ctx.index)),
+            generic_args: vec![],
+        };
+        validations::check_invalid_const_expr_type(&u32_type, count, ctx);
+        if self.count_buffer(ctx.index).is_some() {
+            return;
+        }
+        if let Some(invalid_node) = count.invalid_constant(ctx.index) {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                invalid_node,
+                "invalid invocation count",
+                Some("cannot be used in an invocation count"),
+                &[(count, "count must be a constant or the length of a buffer")],
+            ));
+        }
+    }
+}
+
+impl Dispatch {
+    pub(crate) fn invocation_count(&self, ctx: &TranspilationContext<'_>) -> InvocationCount {
+        match &*self.count {
+            DispatchCount::Indirect(count) => InvocationCount::Indirect(
+                count
+                    .buffer(ctx.index)
+                    .expect("internal error: indirect count buffer not found")
+                    .item_path(ctx.root_path),
+            ),
+            DispatchCount::Direct(count) => {
+                if let Some(buffer) = self.count_buffer(ctx.index) {
+                    InvocationCount::BufferLength(buffer.item_path(ctx.root_path))
+                } else {
+                    InvocationCount::Fixed(
+                        count.parse_const_u32(&mut ConstantContext::new(ctx.index)),
+                    )
+                }
+            }
+        }
+    }
+
+    // All invocations of a workgroup are in range when the count is a multiple of the workgroup size.
+    fn is_uniform(&self, index: &NodeIndex) -> bool {
+        self.count_buffer(index).is_none()
+            && matches!(
+                self.count.evaluate_constant(&mut ConstantContext::new(index)),
+                Some(ConstantValue {
+                    data: ConstantData::U32(count),
+                    ..
+                }) if count % WORKGROUP_SIZE == 0
+            )
+    }
+
+    /// Returns whether the block is possibly run by more than one invocation.
+    pub(crate) fn is_parallel(&self, index: &NodeIndex) -> bool {
+        !matches!(
+            self.count.evaluate_constant(&mut ConstantContext::new(index)),
+            Some(ConstantValue {
+                data: ConstantData::U32(count),
+                ..
+            }) if count <= 1
+        )
+    }
+
+    pub(crate) fn invocation_index(&self) -> Option<&InvocationIndex> {
+        self.index.iter().next().map(|group| &*group.index)
+    }
+
+    // The count is the length of a buffer only for `lenof(<runtime-sized array buffer>)`.
+    fn count_buffer<'a>(&'a self, index: &'a NodeIndex) -> Option<&'a BufferItem> {
+        self.count.as_direct()?.as_lenof()?.buffer(index)
+    }
+}
+
+choice!(
+    enum DispatchCount {
+        Indirect(IndirectCount),
+        Direct(MaybeBinaryExpr),
+    }
+);
+
+// An invocation count read from a buffer when the shader is run.
+sequence!(
+    struct IndirectCount {
+        indirect: IndirectKeyword,
+        #[force_error(true)]
+        start: OpenParenthesisSymbol,
+        buffer: MaybeBinaryExpr,
+        end: CloseParenthesisSymbol,
+    }
+);
+
+impl NodeConfig for IndirectCount {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        let var = self.buffer.as_var_ident();
+        if var.is_some_and(|var| var.source(ctx.index).is_none()) {
+            return;
+        }
+        if self.buffer(ctx.index).is_none() {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.buffer,
+                "invalid indirect invocation count",
+                Some("expression should be a buffer"),
+                &[],
+            ));
+            return;
+        }
+        let Some(type_) = self.buffer.type_(ctx.index) else {
+            return;
+        };
+        let u32_type = NodeSource {
+            node: NodeRef::Type(U32Literal::u32_type(self, ctx.index)),
+            generic_args: vec![],
+        };
+        if type_.are_same_types(&u32_type, ctx.index) != Some(true) && !self.is_atomic(ctx.index) {
+            let type_name = type_.name_or_no_return(ctx.index);
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.buffer,
+                "invalid expression type",
+                Some(&format!(
+                    "expression type is `{type_name}` but expected type is `u32` or `atomic<u32>`"
+                )),
+                &[],
+            ));
+        }
+    }
+
+    fn invalid_constant(&self, _index: &NodeIndex) -> Option<&dyn Node> {
+        Some(self)
+    }
+
+    fn evaluate_constant(&self, _ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        None
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let buffer = self.buffer.transpile(ctx, generic_args);
+        if self.is_atomic(ctx.index) {
+            format!("atomicLoad(&{buffer})")
+        } else {
+            buffer
+        }
+    }
+}
+
+impl IndirectCount {
+    fn buffer<'a>(&'a self, index: &'a NodeIndex) -> Option<&'a BufferItem> {
+        let source = self.buffer.as_var_ident()?.source(index)?.node();
+        (source as &dyn Any).downcast_ref::<BufferItem>()
+    }
+
+    fn is_atomic(&self, index: &NodeIndex) -> bool {
+        self.buffer.type_(index).is_some_and(|type_| {
+            type_ref::is_atomic(&type_) && type_.name_or_no_return(index) == "atomic<u32>"
+        })
+    }
+}
+
+sequence!(
+    struct InvocationIndexGroup {
+        start: PipeSymbol,
+        #[force_error(true)]
+        index: InvocationIndex,
+        end: PipeSymbol,
+    }
+);
+
+impl NodeConfig for InvocationIndexGroup {}
+
+sequence!(
+    #[allow(unused_mut)]
+    struct InvocationIndex {
+        ident: Ident,
+    }
+);
+
+impl NodeConfig for InvocationIndex {
+    fn key(&self) -> Option<String> {
+        Some(sources::variable_key(&self.ident))
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        Some(NodeSource {
+            node: NodeRef::Type(U32Literal::u32_type(self, index)),
+            generic_args: vec![],
+        })
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+}
+
+fn check_dispatch_uniformity(
+    dispatch: Option<&Dispatch>,
+    block: &NonReturnBlock,
+    ctx: &mut ValidationContext<'_>,
+) {
+    let Some(dispatch) = dispatch.filter(|dispatch| !dispatch.is_uniform(ctx.index)) else {
+        return;
+    };
+    let non_uniform_barrier_ids = block
+        .inner
+        .non_uniform_barrier_stmts(ctx.index)
+        .into_iter()
+        .map(|barrier| barrier.id)
+        .collect::<Vec<_>>();
+    for barrier in block.inner.barrier_stmts(ctx.index) {
+        if !non_uniform_barrier_ids.contains(&barrier.id) {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                barrier,
+                "barrier in non-uniform control flow",
+                Some("barrier possibly not reached by all invocations"),
+                &[(
+                    &*dispatch.count,
+                    "invocation count is not a constant multiple of `WORKGROUP_SIZE`",
+                )],
+            ));
+        }
+    }
+}
+
+fn transpile_shader(
+    item: &impl Node,
+    dispatch: Option<&Dispatch>,
+    block: &NonReturnBlock,
+    ctx: &mut TranspilationContext<'_>,
+) -> String {
+    let dependencies = transpiled_dependencies(ctx, item);
+    let shared_vars = block
+        .inner
+        .shared_def_stmts()
+        .map(|shared_def| shared_def.transpile_declaration(ctx))
+        .join("\n");
+    let Some(dispatch) = dispatch else {
+        return format!(
+            indoc!(
+                "{dependencies}
+                {shared_vars}
+
+                @compute
+                @workgroup_size(1, 1, 1)
+                fn main() {{
+                {block}
+                }}"
+            ),
+            dependencies = dependencies,
+            shared_vars = shared_vars,
+            block = block.transpile(ctx, &vec![]),
+        );
+    };
+    let count = match &*dispatch.count {
+        DispatchCount::Direct(count) if dispatch.count_buffer(ctx.index).is_none() => {
+            let count = count.parse_const_u32(&mut ConstantContext::new(ctx.index));
+            format!("{count}u")
+        }
+        DispatchCount::Direct(_) | DispatchCount::Indirect(_) => {
+            dispatch.count.transpile(ctx, &vec![])
+        }
+    };
+    let index_id = dispatch
+        .index
+        .iter()
+        .next()
+        .map_or_else(|| ctx.next_node_id(), |group| group.index.id);
+    // Out of range invocations are detected per workgroup when possible,
+    // so that the control flow stays uniform for barriers.
+    let guarded_index = if dispatch.is_uniform(ctx.index) {
+        format!("(workgroup_id.x + workgroup_id.y * workgroup_count.x) * {WORKGROUP_SIZE}u")
+    } else {
+        format!("_{index_id}")
+    };
+    // The invocation index follows the workgroup layout of `exec::resources::workgroup_count`.
+    format!(
+        indoc!(
+            "{dependencies}
+            {shared_vars}
+
+            @compute
+            @workgroup_size({workgroup_size}, 1, 1)
+            fn main(
+                @builtin(global_invocation_id) invocation_id: vec3<u32>,
+                @builtin(workgroup_id) workgroup_id: vec3<u32>,
+                @builtin(num_workgroups) workgroup_count: vec3<u32>,
+            ) {{
+            let _{index_id} = invocation_id.x + invocation_id.y * workgroup_count.x * {workgroup_size}u;
+            if {guarded_index} >= {count} {{
+            return;
+            }}
+            {block}
+            }}"
+        ),
+        dependencies = dependencies,
+        shared_vars = shared_vars,
+        workgroup_size = WORKGROUP_SIZE,
+        index_id = index_id,
+        guarded_index = guarded_index,
+        count = count,
+        block = block.transpile(ctx, &vec![]),
+    )
+}
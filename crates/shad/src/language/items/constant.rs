@@ -69,7 +69,7 @@ impl NodeConfig for ConstantItem {
     }
 }
 
-fn transpile_constant_value(value: &ConstantValue) -> String {
+pub(crate) fn transpile_constant_value(value: &ConstantValue) -> String {
     let type_name = &value.transpiled_type_name;
     let data = match &value.data {
         ConstantData::F32(value) => format!("{value}"),
@@ -81,6 +81,7 @@ fn transpile_constant_value(value: &ConstantValue) -> String {
             .filter(|field| !field.is_alias)
             .map(|field| transpile_constant_value(&field.value))
             .join(", "),
+        ConstantData::Array(items) => items.iter().map(transpile_constant_value).join(", "),
     };
     format!("{type_name}({data})")
 }
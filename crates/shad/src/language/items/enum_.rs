@@ -0,0 +1,642 @@
+use crate::compilation::index::NodeIndex;
+use crate::compilation::node::{sequence, GenericArgs, Node, NodeConfig, NodeSource, Repeated};
+use crate::compilation::transpilation::TranspilationContext;
+use crate::compilation::validation::ValidationContext;
+use crate::language::items::type_;
+use crate::language::items::type_::{
+    GenericParam, GenericParams, StructField, StructItem, TypeItem,
+};
+use crate::language::keywords::{
+    CloseCurlyBracketSymbol, CloseParenthesisSymbol, CommaSymbol, EnumKeyword,
+    OpenCurlyBracketSymbol, OpenParenthesisSymbol, PubKeyword,
+};
+use crate::language::patterns::Ident;
+use crate::language::type_ref;
+use crate::language::type_ref::{ArrayType, Type};
+use crate::language::{sources, validations};
+use crate::ValidationError;
+use indoc::indoc;
+use itertools::Itertools;
+use std::any::Any;
+use std::iter;
+
+const TAG_SIZE: u32 = 4;
+const WORD_SIZE: u32 = 4;
+
+sequence!(
+    struct EnumItem {
+        pub_: Repeated<PubKeyword, 0, 1>,
+        enum_: EnumKeyword,
+        #[force_error(true)]
+        ident: Ident,
+        generics: Repeated<GenericParams, 0, 1>,
+        variants_start: OpenCurlyBracketSymbol,
+        variants: EnumVariantGroup,
+        variants_end: CloseCurlyBracketSymbol,
+    }
+);
+
+impl NodeConfig for EnumItem {
+    fn key(&self) -> Option<String> {
+        Some(sources::type_key(&self.ident))
+    }
+
+    fn is_public(&self) -> bool {
+        self.pub_.iter().len() > 0
+    }
+
+    fn generic_params(&self) -> Vec<&GenericParam> {
+        self.generics
+            .iter()
+            .flat_map(|params| params.params())
+            .collect()
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        validations::check_duplicated_items(self, ctx);
+        validations::check_recursive_items(self, ctx);
+        for variant in self.variants() {
+            for other_variant in self.variants() {
+                if other_variant.id < variant.id && other_variant.ident.slice == variant.ident.slice
+                {
+                    ctx.errors.push(ValidationError::error(
+                        ctx,
+                        variant,
+                        "enum variant defined multiple times",
+                        Some("duplicated variant"),
+                        &[(other_variant, "same variant defined here")],
+                    ));
+                }
+            }
+            if let Some(payload) = variant.payload.iter().next() {
+                let is_packable = payload
+                    .type_
+                    .type_(ctx.index)
+                    .is_none_or(|type_| is_packable(&type_, ctx.index));
+                if !is_packable {
+                    ctx.errors.push(ValidationError::error(
+                        ctx,
+                        &*payload.type_,
+                        "unsupported enum payload type",
+                        Some("native types in payloads must be scalars or vectors"),
+                        &[],
+                    ));
+                }
+            }
+        }
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        true
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let name = self.transpiled_name(ctx.index, generic_args);
+        let word_count = self.payload_word_count(ctx.index, generic_args);
+        let payload_field = if word_count > 0 {
+            format!("\npayload: array<u32, {word_count}>,")
+        } else {
+            String::new()
+        };
+        let payload_comparison = if word_count > 0 {
+            format!(
+                "for (var i = 0u; i < {word_count}u; i++) {{\n\
+                is_equal = is_equal && left.payload[i] == right.payload[i];\n\
+                }}"
+            )
+        } else {
+            String::new()
+        };
+        let variant_fns = self
+            .variants()
+            .map(|variant| self.transpile_variant_fns(variant, ctx.index, generic_args))
+            .join("\n");
+        format!(
+            indoc!(
+                "struct {name} {{
+                tag: u32,{payload_field}
+                }}
+
+                fn {name}_eq(left: {name}, right: {name}) -> bool {{
+                var is_equal = left.tag == right.tag;
+                {payload_comparison}
+                return is_equal;
+                }}
+
+                {variant_fns}
+                "
+            ),
+            name = name,
+            payload_field = payload_field,
+            payload_comparison = payload_comparison,
+            variant_fns = variant_fns,
+        )
+    }
+}
+
+impl TypeItem for EnumItem {
+    fn is_native(&self) -> bool {
+        false
+    }
+
+    fn name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        type_::name(&self.ident, index, generic_args)
+    }
+
+    fn is_same_item(&self, other: &dyn TypeItem, _index: &NodeIndex) -> Option<bool> {
+        Some(self.id == other.id)
+    }
+
+    fn fields(&self) -> Vec<&StructField> {
+        vec![]
+    }
+
+    fn field(&self, _field_name: &str) -> Option<&StructField> {
+        None
+    }
+
+    // The payload is stored after the tag as 32-bit words, and has the size of the largest one.
+    fn size(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        TAG_SIZE + self.payload_word_count(index, generic_args) * WORD_SIZE
+    }
+
+    fn alignment(&self, _index: &NodeIndex, _generic_args: &GenericArgs<'_>) -> u32 {
+        WORD_SIZE
+    }
+
+    fn transpiled_name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        type_::transpiled_name(self.id, index, generic_args)
+    }
+
+    // coverage: off (enums have no field)
+    fn transpiled_field_name(&self, _field_name: &str) -> String {
+        unreachable!("enums have no field")
+    }
+    // coverage: on
+}
+
+impl EnumItem {
+    pub(crate) fn variants(&self) -> impl Iterator<Item = &EnumVariant> + Clone {
+        self.variants.iter()
+    }
+
+    pub(crate) fn variant(&self, name: &str) -> Option<&EnumVariant> {
+        self.variants().find(|variant| variant.ident.slice == name)
+    }
+
+    pub(crate) fn tag(&self, variant: &EnumVariant) -> usize {
+        self.variants()
+            .position(|other| other.id == variant.id)
+            .expect("internal error: variant not found")
+    }
+
+    /// Returns the WGSL function creating a value of a variant.
+    pub(crate) fn transpiled_variant_fn_name(
+        &self,
+        variant: &EnumVariant,
+        index: &NodeIndex,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let name = self.transpiled_name(index, generic_args);
+        format!("{name}_variant{}", self.tag(variant))
+    }
+
+    /// Returns the WGSL function extracting the payload of a variant.
+    pub(crate) fn transpiled_payload_fn_name(
+        &self,
+        variant: &EnumVariant,
+        index: &NodeIndex,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let name = self.transpiled_name(index, generic_args);
+        format!("{name}_payload{}", self.tag(variant))
+    }
+
+    /// Returns the WGSL function comparing two values of the enum.
+    pub(crate) fn transpiled_eq_fn_name(
+        &self,
+        index: &NodeIndex,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let name = self.transpiled_name(index, generic_args);
+        format!("{name}_eq")
+    }
+
+    fn payload_word_count(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        self.variants()
+            .filter_map(|variant| variant.payload_type(index, generic_args))
+            .map(|type_| word_count(type_::size(&type_, index)))
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn transpile_variant_fns(
+        &self,
+        variant: &EnumVariant,
+        index: &NodeIndex,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let name = self.transpiled_name(index, generic_args);
+        let tag = self.tag(variant);
+        let variant_fn_name = self.transpiled_variant_fn_name(variant, index, generic_args);
+        let Some(payload_type) = variant.payload_type(index, generic_args) else {
+            return format!(
+                indoc!(
+                    "fn {fn_name}() -> {name} {{
+                    var value: {name};
+                    value.tag = {tag}u;
+                    return value;
+                    }}
+                    "
+                ),
+                fn_name = variant_fn_name,
+                name = name,
+                tag = tag,
+            );
+        };
+        let payload_type_name = payload_type.transpiled_type_name(index);
+        format!(
+            indoc!(
+                "fn {variant_fn_name}(payload: {payload_type}) -> {name} {{
+                var value: {name};
+                value.tag = {tag}u;
+                {packing}
+                return value;
+                }}
+
+                fn {payload_fn_name}(value: {name}) -> {payload_type} {{
+                var payload: {payload_type};
+                {unpacking}
+                return payload;
+                }}
+                "
+            ),
+            variant_fn_name = variant_fn_name,
+            payload_fn_name = self.transpiled_payload_fn_name(variant, index, generic_args),
+            payload_type = payload_type_name,
+            name = name,
+            tag = tag,
+            packing = transpile_packing(&payload_type, "payload", "value.payload", "0u", 0, index)
+                .join("\n"),
+            unpacking =
+                transpile_unpacking(&payload_type, "payload", "value.payload", "0u", 0, index)
+                    .join("\n"),
+        )
+    }
+}
+
+sequence!(
+    struct EnumVariantGroup {
+        first_variant: EnumVariant,
+        #[force_error(true)]
+        other_variants: Repeated<EnumOtherVariant, 0, { usize::MAX }>,
+        final_comma: Repeated<CommaSymbol, 0, 1>,
+    }
+);
+
+impl NodeConfig for EnumVariantGroup {}
+
+impl EnumVariantGroup {
+    fn iter(&self) -> impl Iterator<Item = &EnumVariant> + Clone {
+        iter::once(&self.first_variant)
+            .chain(self.other_variants.iter().map(|other| &other.variant))
+            .map(|variant| &**variant)
+    }
+}
+
+sequence!(
+    #[allow(unused_mut)]
+    struct EnumOtherVariant {
+        comma: CommaSymbol,
+        variant: EnumVariant,
+    }
+);
+
+impl NodeConfig for EnumOtherVariant {}
+
+sequence!(
+    #[allow(unused_mut)]
+    struct EnumVariant {
+        ident: Ident,
+        payload: Repeated<EnumVariantPayload, 0, 1>,
+    }
+);
+
+impl NodeConfig for EnumVariant {}
+
+impl EnumVariant {
+    pub(crate) fn has_payload(&self) -> bool {
+        self.payload.iter().len() > 0
+    }
+
+    pub(crate) fn payload_type<'a>(
+        &'a self,
+        index: &'a NodeIndex,
+        generic_args: &GenericArgs<'a>,
+    ) -> Option<NodeSource<'a>> {
+        Some(
+            self.payload
+                .iter()
+                .next()?
+                .type_
+                .type_(index)?
+                .specialized(generic_args, index),
+        )
+    }
+}
+
+sequence!(
+    struct EnumVariantPayload {
+        start: OpenParenthesisSymbol,
+        #[force_error(true)]
+        type_: Type,
+        end: CloseParenthesisSymbol,
+    }
+);
+
+impl NodeConfig for EnumVariantPayload {}
+
+pub(crate) fn enum_item<'a>(type_: &NodeSource<'a>) -> Option<&'a EnumItem> {
+    (type_.type_item()? as &dyn Any).downcast_ref::<EnumItem>()
+}
+
+/// Checks that the generic payloads of an enum specialized with concrete types can be packed.
+pub(crate) fn check_specialized_payloads(
+    node: &dyn Node,
+    item: &dyn TypeItem,
+    generic_args: &GenericArgs<'_>,
+    ctx: &mut ValidationContext<'_>,
+) {
+    let Some(enum_) = (item as &dyn Any).downcast_ref::<EnumItem>() else {
+        return;
+    };
+    if generic_args
+        .iter()
+        .any(|arg| arg.as_ref().is_none_or(NodeSource::is_generic))
+    {
+        return;
+    }
+    for payload in enum_
+        .variants()
+        .filter_map(|variant| variant.payload.iter().next())
+    {
+        let Some(type_) = payload.type_.type_(ctx.index) else {
+            continue;
+        };
+        let specialized = type_.specialized(generic_args, ctx.index);
+        if type_.is_generic() && !is_packable(&specialized, ctx.index) {
+            let type_name = specialized.name_or_no_return(ctx.index);
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                node,
+                "unsupported enum payload type",
+                Some(&format!("`{type_name}` cannot be stored in a payload")),
+                &[(
+                    &*payload.type_,
+                    "native types in payloads must be scalars or vectors",
+                )],
+            ));
+        }
+    }
+}
+
+// Native values are stored in payload words with `bitcast`, which is only supported for 32-bit
+// scalars and vectors.
+fn is_packable(type_: &NodeSource<'_>, index: &NodeIndex) -> bool {
+    let Some(item) = type_.type_item() else {
+        return true;
+    };
+    if type_.is_generic() || (item as &dyn Any).is::<EnumItem>() {
+        true
+    } else if let Some(item_type) = type_ref::array_item_type(type_) {
+        is_packable(&item_type, index)
+    } else if type_ref::matrix_columns(type_, index).is_some() || type_ref::is_atomic(type_) {
+        false
+    } else if let Some(fields) = composite_fields(type_, index) {
+        fields
+            .iter()
+            .all(|(_, field_type)| is_packable(field_type, index))
+    } else {
+        let size = type_::size(type_, index);
+        size.is_multiple_of(WORD_SIZE) && (1..=4).contains(&word_count(size))
+    }
+}
+
+fn transpile_packing(
+    type_: &NodeSource<'_>,
+    value: &str,
+    words: &str,
+    offset: &str,
+    depth: usize,
+    index: &NodeIndex,
+) -> Vec<String> {
+    let item = type_
+        .type_item()
+        .expect("internal error: invalid payload type") as &dyn Any;
+    if let Some(enum_) = item.downcast_ref::<EnumItem>() {
+        let word_count = enum_.payload_word_count(index, &type_.generic_args);
+        let mut stmts = vec![format!("{words}[{offset}] = {value}.tag;")];
+        if word_count > 0 {
+            let i = format!("i{depth}");
+            let payload_offset = word_offset(offset, 1);
+            stmts.push(format!(
+                "for (var {i} = 0u; {i} < {word_count}u; {i}++) {{\n\
+                {words}[{payload_offset} + {i}] = {value}.payload[{i}];\n\
+                }}"
+            ));
+        }
+        stmts
+    } else if item.is::<ArrayType>() {
+        transpile_array_copy(
+            type_,
+            value,
+            offset,
+            depth,
+            index,
+            |item_type, item, offset| {
+                transpile_packing(item_type, item, words, offset, depth + 1, index)
+            },
+        )
+    } else if let Some(fields) = composite_fields(type_, index) {
+        transpile_fields_copy(
+            &fields,
+            value,
+            offset,
+            index,
+            |field_type, field, offset| {
+                transpile_packing(field_type, field, words, offset, depth, index)
+            },
+        )
+    } else {
+        let word_count = word_count(type_::size(type_, index));
+        if word_count == 1 {
+            vec![format!("{words}[{offset}] = bitcast<u32>({value});")]
+        } else {
+            (0..word_count)
+                .map(|i| {
+                    let offset = word_offset(offset, i);
+                    format!("{words}[{offset}] = bitcast<vec{word_count}<u32>>({value})[{i}];")
+                })
+                .collect()
+        }
+    }
+}
+
+fn transpile_unpacking(
+    type_: &NodeSource<'_>,
+    value: &str,
+    words: &str,
+    offset: &str,
+    depth: usize,
+    index: &NodeIndex,
+) -> Vec<String> {
+    let item = type_
+        .type_item()
+        .expect("internal error: invalid payload type") as &dyn Any;
+    if let Some(enum_) = item.downcast_ref::<EnumItem>() {
+        let word_count = enum_.payload_word_count(index, &type_.generic_args);
+        let mut stmts = vec![format!("{value}.tag = {words}[{offset}];")];
+        if word_count > 0 {
+            let i = format!("i{depth}");
+            let payload_offset = word_offset(offset, 1);
+            stmts.push(format!(
+                "for (var {i} = 0u; {i} < {word_count}u; {i}++) {{\n\
+                {value}.payload[{i}] = {words}[{payload_offset} + {i}];\n\
+                }}"
+            ));
+        }
+        stmts
+    } else if item.is::<ArrayType>() {
+        transpile_array_copy(
+            type_,
+            value,
+            offset,
+            depth,
+            index,
+            |item_type, item, offset| {
+                transpile_unpacking(item_type, item, words, offset, depth + 1, index)
+            },
+        )
+    } else if let Some(fields) = composite_fields(type_, index) {
+        transpile_fields_copy(
+            &fields,
+            value,
+            offset,
+            index,
+            |field_type, field, offset| {
+                transpile_unpacking(field_type, field, words, offset, depth, index)
+            },
+        )
+    } else {
+        let type_name = type_.transpiled_type_name(index);
+        let word_count = word_count(type_::size(type_, index));
+        if word_count == 1 {
+            vec![format!(
+                "{value} = bitcast<{type_name}>({words}[{offset}]);"
+            )]
+        } else {
+            let components = (0..word_count)
+                .map(|i| format!("{words}[{}]", word_offset(offset, i)))
+                .join(", ");
+            vec![format!(
+                "{value} = bitcast<{type_name}>(vec{word_count}<u32>({components}));"
+            )]
+        }
+    }
+}
+
+fn transpile_array_copy(
+    type_: &NodeSource<'_>,
+    value: &str,
+    offset: &str,
+    depth: usize,
+    index: &NodeIndex,
+    transpile_item: impl Fn(&NodeSource<'_>, &str, &str) -> Vec<String>,
+) -> Vec<String> {
+    let item_type = type_ref::array_item_type(type_).expect("internal error: invalid array");
+    let length = type_ref::array_length(type_).expect("internal error: invalid array length");
+    let stride = word_count(type_::round_up(
+        type_::alignment(&item_type, index),
+        type_::size(&item_type, index),
+    ));
+    let i = format!("i{depth}");
+    let item_stmts = transpile_item(
+        &item_type,
+        &format!("{value}[{i}]"),
+        &format!("{offset} + {i} * {stride}u"),
+    )
+    .join("\n");
+    vec![format!(
+        "for (var {i} = 0u; {i} < {length}u; {i}++) {{\n{item_stmts}\n}}"
+    )]
+}
+
+// Returns the transpiled name and the type of each field of a struct or tuple type.
+fn composite_fields<'a>(
+    type_: &NodeSource<'a>,
+    index: &'a NodeIndex,
+) -> Option<Vec<(String, NodeSource<'a>)>> {
+    if let Some(item_types) = type_ref::tuple_item_types(type_) {
+        item_types
+            .into_iter()
+            .enumerate()
+            .map(|(position, item_type)| Some((format!("_{position}"), item_type?)))
+            .collect()
+    } else {
+        let item = type_.type_item()?;
+        let struct_ = (item as &dyn Any).downcast_ref::<StructItem>()?;
+        Some(
+            struct_
+                .fields()
+                .into_iter()
+                .map(|field| {
+                    (
+                        struct_.transpiled_field_name(&field.ident.slice),
+                        field.specialized_type(index, &type_.generic_args),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+fn transpile_fields_copy(
+    fields: &[(String, NodeSource<'_>)],
+    value: &str,
+    offset: &str,
+    index: &NodeIndex,
+    transpile_field: impl Fn(&NodeSource<'_>, &str, &str) -> Vec<String>,
+) -> Vec<String> {
+    let field_types: Vec<_> = fields
+        .iter()
+        .map(|(_, field_type)| field_type.clone())
+        .collect();
+    fields
+        .iter()
+        .enumerate()
+        .flat_map(|(i, (field_name, field_type))| {
+            let field_offset = word_count(type_::field_offset(&field_types[..=i], index));
+            transpile_field(
+                field_type,
+                &format!("{value}.{field_name}"),
+                &word_offset(offset, field_offset),
+            )
+        })
+        .collect()
+}
+
+fn word_count(size: u32) -> u32 {
+    size.div_ceil(WORD_SIZE)
+}
+
+fn word_offset(offset: &str, word_count: u32) -> String {
+    if word_count == 0 {
+        offset.into()
+    } else {
+        format!("{offset} + {word_count}u")
+    }
+}
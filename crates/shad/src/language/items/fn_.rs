@@ -1,4 +1,4 @@
-use crate::compilation::constant::{ConstantContext, ConstantValue};
+use crate::compilation::constant::{ConstantContext, ConstantInterruption, ConstantValue};
 use crate::compilation::index::NodeIndex;
 use crate::compilation::node::{
     sequence, GenericArgs, Node, NodeConfig, NodeRef, NodeSource, Repeated,
@@ -6,11 +6,15 @@ use crate::compilation::node::{
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
 use crate::language::items::block::Block;
+use crate::language::items::interface::InterfaceFnItem;
+use crate::language::items::type_;
+use crate::language::items::type_::{GenericParam, GenericParams};
 use crate::language::keywords::{
     ArrowSymbol, CloseParenthesisSymbol, ColonSymbol, CommaSymbol, ConstKeyword, EqSymbol,
     FnKeyword, NativeKeyword, OpenParenthesisSymbol, PubKeyword, RefKeyword, SemicolonSymbol,
 };
 use crate::language::patterns::{Ident, StringLiteral};
+use crate::language::statements::Stmt;
 use crate::language::type_ref::Type;
 use crate::language::{constants, sources, validations};
 use crate::ValidationError;
@@ -42,6 +46,10 @@ impl NodeConfig for NativeFnItem {
         self.pub_.iter().len() > 0
     }
 
+    fn generic_params(&self) -> Vec<&GenericParam> {
+        self.signature.generic_params().collect()
+    }
+
     fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
         self.signature.is_ref(index)
     }
@@ -51,10 +59,20 @@ impl NodeConfig for NativeFnItem {
     }
 
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
-        let params = self.signature.params().map(|p| p.ident.slice.as_str());
+        let params = self
+            .signature
+            .params()
+            .map(|p| p.ident.slice.as_str())
+            .chain(
+                self.signature
+                    .generic_params()
+                    .map(|p| p.ident.slice.as_str()),
+            );
         validations::check_duplicated_items(self, ctx);
         validations::check_native_code(&self.transpilation, params, ctx);
+        // The constant implementation of generic functions is only checked once specialized.
         if self.const_.iter().len() > 0
+            && self.signature.generics.iter().len() == 0
             && constants::native_fn_runner(&self.signature.fn_key()).is_none()
         {
             ctx.errors.push(ValidationError::error(
@@ -76,13 +94,26 @@ impl NodeConfig for NativeFnItem {
                     .expect("internal error: not found const fn arg variable")
             })
             .collect::<Vec<_>>();
-        let return_type = self.type_(ctx.index)?;
+        let return_type = ctx.specialized(&self.type_(ctx.index)?);
         if return_type.is_no_return() {
             unreachable!("constant expressions always return a value")
         } else {
+            let Some(runner) = constants::native_fn_runner(&self.specialized_key(ctx)?) else {
+                ctx.interruption = Some(ConstantInterruption::MissingNativeFnImplementation);
+                return None;
+            };
+            if constants::is_division_by_zero(&self.signature.ident.slice, &params) {
+                ctx.interruption = Some(ConstantInterruption::DivisionByZero);
+                return None;
+            }
+            let data = runner(&params);
+            if !data.is_finite() {
+                ctx.interruption = Some(ConstantInterruption::NonFiniteValue);
+                return None;
+            }
             Some(ConstantValue {
                 transpiled_type_name: return_type.transpiled_type_name(ctx.index),
-                data: constants::native_fn_runner(&self.key()?)?(&params),
+                data,
             })
         }
     }
@@ -92,6 +123,25 @@ impl NodeConfig for NativeFnItem {
     }
 }
 
+impl NativeFnItem {
+    // Generic functions use the constant implementation of their specialized parameter types.
+    fn specialized_key(&self, ctx: &ConstantContext<'_>) -> Option<String> {
+        if self.signature.generics.iter().len() == 0 {
+            return self.key();
+        }
+        let param_types = self
+            .signature
+            .params()
+            .map(|param| Some(ctx.specialized(&param.type_(ctx.index)?)))
+            .collect::<Option<Vec<_>>>()?;
+        Some(sources::fn_key_from_operator(
+            &self.signature.ident.slice,
+            param_types,
+            ctx.index,
+        ))
+    }
+}
+
 sequence!(
     struct FnItem {
         pub_: Repeated<PubKeyword, 0, 1>,
@@ -111,6 +161,10 @@ impl NodeConfig for FnItem {
         self.pub_.iter().len() > 0
     }
 
+    fn generic_params(&self) -> Vec<&GenericParam> {
+        self.signature.generic_params().collect()
+    }
+
     fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
         self.signature.is_ref(index)
     }
@@ -125,9 +179,8 @@ impl NodeConfig for FnItem {
         if let Some(const_kw) = self.const_.iter().next() {
             validations::check_invalid_const_scope(&*self.body, &**const_kw, ctx);
         }
-        let return_stmt = self.body.last_stmt().and_then(|stmt| stmt.as_return());
         let return_type = self.signature.return_type.iter().next();
-        if let (None, Some(return_type)) = (return_stmt, return_type) {
+        if let (false, Some(return_type)) = (self.body.is_returning(), return_type) {
             ctx.errors.push(ValidationError::error(
                 ctx,
                 &*self.body,
@@ -136,18 +189,57 @@ impl NodeConfig for FnItem {
                 &[(&**return_type, "the function has a return type")],
             ));
         }
-        if let (Some(return_stmt), Some(expected_type)) = (return_stmt, self.type_(ctx.index)) {
-            if let Some(actual_type) = return_stmt.type_(ctx.index) {
+        validations::check_loop_control_stmts(&self.body, ctx);
+        for stmt in self.body.nested_stmts() {
+            if let Some(shared_def) = stmt.as_shared_def() {
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    shared_def,
+                    "`shared` variable defined in a function",
+                    Some("only allowed at the top level of a `run` or `init` block"),
+                    &[],
+                ));
+            }
+        }
+        for barrier in self.body.barrier_stmts(ctx.index) {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                barrier,
+                "barrier in a function",
+                Some("only allowed in a `run` or `init` block"),
+                &[],
+            ));
+        }
+        for return_stmt in self.body.return_stmts() {
+            let is_conditional = self.body.statements.iter().all(|stmt| {
+                stmt.as_return()
+                    .is_none_or(|stmt| stmt.id != return_stmt.id)
+            });
+            if is_conditional && self.is_ref(ctx.index) == Some(true) {
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    return_stmt,
+                    "conditional `return` statement in a function returning a reference",
+                    Some("only allowed at the end of the function body"),
+                    &[],
+                ));
+            }
+            if self.is_ref(ctx.index) == Some(false) {
+                validations::check_copyable_expr(&*return_stmt.expr, ctx);
+            }
+            if let (Some(expected_type), Some(actual_type)) =
+                (self.type_(ctx.index), return_stmt.type_(ctx.index))
+            {
                 if !actual_type.is_no_return()
-                    && actual_type.are_same_types(&expected_type) == Some(false)
+                    && actual_type.are_same_types(&expected_type, ctx.index) == Some(false)
                 {
-                    let actual_type_name = actual_type.name_or_no_return();
-                    let expected_type_name = expected_type.name_or_no_return();
+                    let actual_type_name = actual_type.name_or_no_return(ctx.index);
+                    let expected_type_name = expected_type.name_or_no_return(ctx.index);
                     ctx.errors.push(ValidationError::error(
                         ctx,
                         &*return_stmt.expr,
                         "invalid returned type",
-                        Some(&format!("returned type is `{actual_type_name}`",)),
+                        Some(&format!("returned type is `{actual_type_name}`")),
                         &[(
                             &*self.signature.return_type,
                             &format!("expected type is `{expected_type_name}`"),
@@ -176,6 +268,7 @@ impl NodeConfig for FnItem {
                 "{signature} {{
                 {param_vars}
                 {body}
+                {unreachable_return}
                 }}"
             ),
             signature = self.signature.transpile(ctx, generic_args),
@@ -185,6 +278,7 @@ impl NodeConfig for FnItem {
                 .map(|param| format!("var _{id} = _p{id};", id = param.id))
                 .join("\n"),
             body = self.body.transpile(ctx, generic_args),
+            unreachable_return = self.transpile_unreachable_return(ctx, generic_args),
         )
     }
 }
@@ -196,6 +290,27 @@ impl FnItem {
             .any(|param| param.is_ref(index) == Some(true))
             || self.is_ref(index) == Some(true)
     }
+
+    // naga considers that a function body ending with a `loop` can reach its end, even if the loop
+    // can only be exited with a `return` statement.
+    fn transpile_unreachable_return(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        match (
+            self.body.last_stmt().and_then(Stmt::as_loop),
+            self.signature.return_type.iter().next(),
+        ) {
+            (Some(_), Some(return_type)) => {
+                format!(
+                    "return {}();",
+                    return_type.type_.transpile(ctx, generic_args)
+                )
+            }
+            _ => String::new(),
+        }
+    }
 }
 
 sequence!(
@@ -203,6 +318,7 @@ sequence!(
         fn_: FnKeyword,
         #[force_error(true)]
         ident: Ident,
+        generics: Repeated<GenericParams, 0, 1>,
         params_start: OpenParenthesisSymbol,
         params: Repeated<FnParamGroup, 0, 1>,
         params_end: CloseParenthesisSymbol,
@@ -252,9 +368,10 @@ impl NodeConfig for FnSignature {
         ctx: &mut TranspilationContext<'_>,
         generic_args: &GenericArgs<'_>,
     ) -> String {
+        let fn_id = self.parent_ids[self.parent_ids.len() - 1];
         format!(
-            "fn _{fn_id}({params}) {return_type}",
-            fn_id = self.parent_ids[self.parent_ids.len() - 1],
+            "fn {name}({params}) {return_type}",
+            name = type_::transpiled_name(fn_id, ctx.index, generic_args),
             params = self
                 .params()
                 .map(|param| param.transpile(ctx, generic_args))
@@ -272,8 +389,16 @@ impl FnSignature {
             .map(|param| &**param)
     }
 
+    pub(crate) fn generic_params(&self) -> impl Iterator<Item = &GenericParam> + Clone {
+        self.generics.iter().flat_map(|generics| generics.params())
+    }
+
     fn fn_key(&self) -> String {
-        sources::fn_key_from_params(&self.ident, &self.params)
+        if self.generics.iter().len() > 0 {
+            sources::generic_fn_key(&self.ident.slice, self.params().count())
+        } else {
+            sources::fn_key_from_params(&self.ident, &self.params)
+        }
     }
 }
 
@@ -371,6 +496,8 @@ pub(crate) fn is_const(fn_item_node: &dyn Node) -> bool {
         fn_.const_.iter().len() == 1
     } else if let Some(fn_) = (fn_item_node as &dyn Any).downcast_ref::<FnItem>() {
         fn_.const_.iter().len() == 1
+    } else if (fn_item_node as &dyn Any).is::<InterfaceFnItem>() {
+        false
     } else {
         unreachable!("unknown fn item")
     }
@@ -381,6 +508,8 @@ pub(crate) fn signature(fn_item_node: &dyn Node) -> &FnSignature {
         &fn_.signature
     } else if let Some(fn_) = (fn_item_node as &dyn Any).downcast_ref::<FnItem>() {
         &fn_.signature
+    } else if let Some(fn_) = (fn_item_node as &dyn Any).downcast_ref::<InterfaceFnItem>() {
+        &fn_.signature
     } else {
         unreachable!("unknown fn item")
     }
@@ -0,0 +1,185 @@
+use crate::compilation::index::NodeIndex;
+use crate::compilation::node::{sequence, Node, NodeConfig, NodeSource, Repeated};
+use crate::compilation::validation::ValidationContext;
+use crate::language::expressions::fn_call;
+use crate::language::items::fn_;
+use crate::language::items::fn_::FnSignature;
+use crate::language::items::type_::{GenericParam, GenericParams};
+use crate::language::keywords::{
+    CloseCurlyBracketSymbol, InterfaceKeyword, NativeKeyword, OpenCurlyBracketSymbol, PubKeyword,
+    SemicolonSymbol,
+};
+use crate::language::patterns::Ident;
+use crate::language::{sources, validations};
+use crate::ValidationError;
+use std::any::Any;
+
+sequence!(
+    struct InterfaceItem {
+        pub_: Repeated<PubKeyword, 0, 1>,
+        native: Repeated<NativeKeyword, 0, 1>,
+        interface: InterfaceKeyword,
+        #[force_error(true)]
+        ident: Ident,
+        generics: GenericParams,
+        fns_start: OpenCurlyBracketSymbol,
+        fns: Repeated<InterfaceFnItem, 0, { usize::MAX }>,
+        fns_end: CloseCurlyBracketSymbol,
+    }
+);
+
+impl NodeConfig for InterfaceItem {
+    fn key(&self) -> Option<String> {
+        Some(sources::interface_key(&self.ident))
+    }
+
+    fn is_public(&self) -> bool {
+        self.pub_.iter().len() > 0
+    }
+
+    fn generic_params(&self) -> Vec<&GenericParam> {
+        self.generics.params().collect()
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        validations::check_duplicated_items(self, ctx);
+        let params: Vec<_> = self.generics.params().collect();
+        if params.len() != 1 || params[0].kind.as_type().is_none() {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.generics,
+                "invalid interface generic parameters",
+                Some("interfaces must have exactly one `type` parameter"),
+                &[],
+            ));
+        }
+    }
+}
+
+impl InterfaceItem {
+    /// Returns whether a type is allowed to implement the interface.
+    ///
+    /// A `native` interface can only be implemented by native types defined in the same module,
+    /// so that functions constrained by it always generate valid WGSL code.
+    pub(crate) fn is_implementable_by(&self, type_: &NodeSource<'_>) -> bool {
+        self.native.iter().len() == 0
+            || type_.type_item().is_none_or(|item| {
+                (item as &dyn Any).is::<GenericParam>()
+                    || (item.is_native() && item.path == self.path)
+            })
+    }
+
+    /// Returns the first interface function not implemented for a type.
+    pub(crate) fn missing_fn<'a>(
+        &'a self,
+        type_: &NodeSource<'a>,
+        index: &'a NodeIndex,
+    ) -> Option<&'a InterfaceFnItem> {
+        let mut fns = self.fns.iter().map(|fn_| &**fn_);
+        let param = type_
+            .type_item()
+            .and_then(|item| (item as &dyn Any).downcast_ref::<GenericParam>());
+        if let Some(param) = param {
+            // The interface parameter itself implements the interface by definition.
+            let is_constrained = self
+                .generics
+                .params()
+                .any(|self_param| self_param.id == param.id)
+                || param
+                    .interface(index)
+                    .is_some_and(|interface| interface.id == self.id);
+            if is_constrained {
+                None
+            } else {
+                fns.next()
+            }
+        } else {
+            fns.find(|fn_| fn_.implementation(type_, index).is_none())
+        }
+    }
+}
+
+sequence!(
+    struct InterfaceFnItem {
+        signature: FnSignature,
+        #[force_error(true)]
+        semicolon: SemicolonSymbol,
+    }
+);
+
+impl NodeConfig for InterfaceFnItem {
+    fn is_ref(&self, index: &NodeIndex) -> Option<bool> {
+        self.signature.is_ref(index)
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.signature.type_(index)
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        if let Some(generics) = self.signature.generics.iter().next() {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &**generics,
+                "generic interface function",
+                Some("interface functions cannot have generic parameters"),
+                &[],
+            ));
+        }
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+}
+
+impl InterfaceFnItem {
+    /// Returns the function implementing this interface function for a concrete type.
+    ///
+    /// The implementation is searched from the module defining the type, then from the module
+    /// defining the interface.
+    pub(crate) fn implementation<'a>(
+        &'a self,
+        type_: &NodeSource<'a>,
+        index: &'a NodeIndex,
+    ) -> Option<NodeSource<'a>> {
+        let generic_args = vec![Some(type_.clone())];
+        let param_types = self
+            .signature
+            .params()
+            .map(|param| Some(param.type_(index)?.specialized(&generic_args, index)))
+            .collect::<Option<Vec<_>>>()?;
+        let expected_type = self.type_(index)?.specialized(&generic_args, index);
+        [type_.node(), self as &dyn Node]
+            .into_iter()
+            .find_map(|node| {
+                let name = &self.signature.ident.slice;
+                let fn_ = fn_call::fn_source(node, name, param_types.clone(), index)?;
+                self.is_implemented_by(&fn_, &expected_type, index)
+                    .then_some(fn_)
+            })
+    }
+
+    fn is_implemented_by(
+        &self,
+        fn_: &NodeSource<'_>,
+        expected_type: &NodeSource<'_>,
+        index: &NodeIndex,
+    ) -> bool {
+        let Some(actual_type) = fn_call::fn_type(fn_, index) else {
+            return false;
+        };
+        let are_same_types = if expected_type.is_no_return() || actual_type.is_no_return() {
+            expected_type.is_no_return() && actual_type.is_no_return()
+        } else {
+            expected_type.are_same_types(&actual_type, index) == Some(true)
+        };
+        let fn_signature = fn_::signature(fn_.node());
+        are_same_types
+            && fn_signature.is_ref(index) == self.signature.is_ref(index)
+            && fn_signature
+                .params()
+                .zip(self.signature.params())
+                .all(|(param1, param2)| param1.is_ref(index) == param2.is_ref(index))
+    }
+}
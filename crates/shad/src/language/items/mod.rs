@@ -1,20 +1,26 @@
 use crate::compilation::index::NodeIndex;
 use crate::compilation::node::{choice, sequence, EndOfFile, Node, NodeConfig, Repeated};
 use crate::compilation::transpilation::TranspilationContext;
+use crate::compilation::validation::ValidationContext;
 use crate::language::items::buffer::BufferItem;
 use crate::language::items::compute::{InitItem, RunItem};
 use crate::language::items::constant::ConstantItem;
+use crate::language::items::enum_::EnumItem;
 use crate::language::items::fn_::{FnItem, NativeFnItem};
 use crate::language::items::import::ImportItem;
+use crate::language::items::interface::InterfaceItem;
 use crate::language::items::type_::{NativeStructItem, StructItem};
+use crate::language::{type_ref, validations};
 use itertools::Itertools;
 
 pub(crate) mod block;
 pub(crate) mod buffer;
 pub(crate) mod compute;
 pub(crate) mod constant;
+pub(crate) mod enum_;
 pub(crate) mod fn_;
 pub(crate) mod import;
+pub(crate) mod interface;
 pub(crate) mod type_;
 
 sequence!(
@@ -27,6 +33,51 @@ sequence!(
 
 impl NodeConfig for Root {}
 
+impl Root {
+    pub(crate) fn validate_constant_evaluations(&self, ctx: &mut ValidationContext<'_>) {
+        for item in self.items.iter() {
+            match &**item {
+                Item::Constant(item) => validations::check_constant_evaluation(&*item.expr, ctx),
+                Item::Init(item) => {
+                    for count in item
+                        .dispatch
+                        .iter()
+                        .filter_map(|dispatch| dispatch.count.as_direct())
+                    {
+                        validations::check_constant_evaluation(count, ctx);
+                    }
+                    for priority in item.priority.iter() {
+                        validations::check_constant_evaluation(&*priority.value, ctx);
+                    }
+                }
+                Item::Run(item) => {
+                    for count in item
+                        .dispatch
+                        .iter()
+                        .filter_map(|dispatch| dispatch.count.as_direct())
+                    {
+                        validations::check_constant_evaluation(count, ctx);
+                    }
+                    for priority in item.priority.iter() {
+                        validations::check_constant_evaluation(&*priority.value, ctx);
+                    }
+                    for frequency in item.frequency.iter() {
+                        validations::check_constant_evaluation(&*frequency.value, ctx);
+                    }
+                }
+                Item::Import(_)
+                | Item::Buffer(_)
+                | Item::NativeFn(_)
+                | Item::Fn(_)
+                | Item::NativeStruct(_)
+                | Item::Struct(_)
+                | Item::Enum(_)
+                | Item::Interface(_) => {}
+            }
+        }
+    }
+}
+
 choice!(
     enum Item {
         Import(ImportItem),
@@ -38,6 +89,8 @@ choice!(
         Fn(FnItem),
         NativeStruct(NativeStructItem),
         Struct(StructItem),
+        Enum(EnumItem),
+        Interface(InterfaceItem),
     }
 );
 
@@ -47,10 +100,19 @@ pub(crate) fn is_item_recursive(item: &impl Node, index: &NodeIndex) -> bool {
         .any(|source| source.node().id == item.id)
 }
 
+// A tuple struct is generated once per distinct tuple signature.
 fn transpiled_dependencies(ctx: &mut TranspilationContext<'_>, item: &impl Node) -> String {
     item.nested_sources(ctx.index)
         .into_iter()
-        .filter(|source| source.node().is_transpilable_dependency(ctx.index))
-        .map(|source| source.node().transpile(ctx, &source.generic_args))
+        .filter_map(|source| {
+            if type_ref::tuple_item_types(&source).is_some() {
+                (!source.is_generic()).then(|| type_ref::transpile_tuple_struct(&source, ctx.index))
+            } else {
+                let node = source.node();
+                node.is_transpilable_dependency(ctx.index)
+                    .then(|| node.transpile(ctx, &source.generic_args))
+            }
+        })
+        .unique()
         .join("\n")
 }
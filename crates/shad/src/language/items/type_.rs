@@ -1,10 +1,13 @@
+use crate::compilation::constant::ConstantContext;
 use crate::compilation::index::NodeIndex;
 use crate::compilation::node::{
-    sequence, GenericArgs, Node, NodeConfig, NodeRef, NodeSource, Repeated,
+    choice, sequence, GenericArgs, Node, NodeConfig, NodeRef, NodeSource, Repeated, UNKNOWN_TYPE,
 };
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
 use crate::language::expressions::binary::MaybeBinaryExpr;
+use crate::language::items::enum_::EnumItem;
+use crate::language::items::interface::InterfaceItem;
 use crate::language::keywords::{
     CloseAngleBracketSymbol, CloseCurlyBracketSymbol, ColonSymbol, CommaSymbol, EqSymbol,
     NativeKeyword, OpenAngleBracketSymbol, OpenCurlyBracketSymbol, PubKeyword, StructKeyword,
@@ -22,17 +25,17 @@ use std::iter;
 pub(crate) trait TypeItem: Node {
     fn is_native(&self) -> bool;
 
-    fn ident(&self) -> &Ident;
+    fn name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String;
 
-    fn generic_params(&self) -> Vec<&GenericParam>;
+    fn is_same_item(&self, other: &dyn TypeItem, index: &NodeIndex) -> Option<bool>;
 
     fn fields(&self) -> Vec<&StructField>;
 
     fn field(&self, field_name: &str) -> Option<&StructField>;
 
-    fn size(&self, index: &NodeIndex) -> u32;
+    fn size(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32;
 
-    fn alignment(&self, index: &NodeIndex) -> u32;
+    fn alignment(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32;
 
     fn transpiled_name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String;
 
@@ -68,6 +71,13 @@ impl NodeConfig for NativeStructItem {
         self.pub_.iter().len() > 0
     }
 
+    fn generic_params(&self) -> Vec<&GenericParam> {
+        self.generics
+            .iter()
+            .flat_map(|params| params.params())
+            .collect()
+    }
+
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
         let u32_type = NodeSource {
             node: NodeRef::Type(U32Literal::u32_type(self, ctx.index)),
@@ -97,15 +107,12 @@ impl TypeItem for NativeStructItem {
         true
     }
 
-    fn ident(&self) -> &Ident {
-        &self.ident
+    fn name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        name(&self.ident, index, generic_args)
     }
 
-    fn generic_params(&self) -> Vec<&GenericParam> {
-        self.generics
-            .iter()
-            .flat_map(|params| params.params())
-            .collect()
+    fn is_same_item(&self, other: &dyn TypeItem, _index: &NodeIndex) -> Option<bool> {
+        Some(self.id == other.id)
     }
 
     // coverage: off (never call in practice)
@@ -123,12 +130,14 @@ impl TypeItem for NativeStructItem {
             .find_map(|fields| fields.field(field_name))
     }
 
-    fn size(&self, index: &NodeIndex) -> u32 {
-        self.size.parse_const_u32(index)
+    fn size(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        self.size
+            .parse_const_u32(&mut ConstantContext::with_generic_args(index, generic_args))
     }
 
-    fn alignment(&self, index: &NodeIndex) -> u32 {
-        self.alignment.parse_const_u32(index)
+    fn alignment(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        self.alignment
+            .parse_const_u32(&mut ConstantContext::with_generic_args(index, generic_args))
     }
 
     fn transpiled_name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
@@ -171,6 +180,13 @@ impl NodeConfig for StructItem {
         self.pub_.iter().len() > 0
     }
 
+    fn generic_params(&self) -> Vec<&GenericParam> {
+        self.generics
+            .iter()
+            .flat_map(|params| params.params())
+            .collect()
+    }
+
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
         validations::check_duplicated_items(self, ctx);
         validations::check_recursive_items(self, ctx);
@@ -222,15 +238,12 @@ impl TypeItem for StructItem {
         false
     }
 
-    fn ident(&self) -> &Ident {
-        &self.ident
+    fn name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        name(&self.ident, index, generic_args)
     }
 
-    fn generic_params(&self) -> Vec<&GenericParam> {
-        self.generics
-            .iter()
-            .flat_map(|params| params.params())
-            .collect()
+    fn is_same_item(&self, other: &dyn TypeItem, _index: &NodeIndex) -> Option<bool> {
+        Some(self.id == other.id)
     }
 
     fn fields(&self) -> Vec<&StructField> {
@@ -241,29 +254,29 @@ impl TypeItem for StructItem {
         self.fields.field(field_name)
     }
 
-    fn size(&self, index: &NodeIndex) -> u32 {
-        let fields: Vec<_> = self.fields.iter().collect();
-        let last_field_size = fields[fields.len() - 1].type_item(index).size(index);
+    fn size(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        let field_types: Vec<_> = self
+            .fields
+            .iter()
+            .map(|field| field.specialized_type(index, generic_args))
+            .collect();
+        let last_field_size = size(&field_types[field_types.len() - 1], index);
         round_up(
-            self.alignment(index),
-            field_offset(&fields, index) + last_field_size,
+            self.alignment(index, generic_args),
+            field_offset(&field_types, index) + last_field_size,
         )
     }
 
-    fn alignment(&self, index: &NodeIndex) -> u32 {
+    fn alignment(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
         self.fields
             .iter()
-            .map(|field| field.type_item(index).alignment(index))
+            .map(|field| alignment(&field.specialized_type(index, generic_args), index))
             .max()
             .expect("internal error: custom structs should have at least one field")
     }
 
     fn transpiled_name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
-        let mut name = format!("_{}", self.id);
-        for arg in generic_args.iter().flatten() {
-            name += &arg.transpiled_type_name(index);
-        }
-        name.replace([' ', '<', '>', ','], "_")
+        transpiled_name(self.id, index, generic_args)
     }
 
     fn transpiled_field_name(&self, field_name: &str) -> String {
@@ -304,21 +317,160 @@ impl NodeConfig for GenericParams {
 }
 
 impl GenericParams {
-    fn params(&self) -> impl Iterator<Item = &GenericParam> + Clone {
+    pub(crate) fn params(&self) -> impl Iterator<Item = &GenericParam> + Clone {
         iter::once(&*self.first_param).chain(self.other_params.iter().map(|other| &*other.param))
     }
 }
 
+// Value parameters are recognized by name, as the bound is either a type or an interface.
+const GENERIC_VALUE_TYPES: &[&str] = &["u32", "i32", "bool"];
+
 sequence!(
     struct GenericParam {
         #[force_error(true)]
         ident: Ident,
         colon: ColonSymbol,
-        type_: TypeKeyword,
+        kind: GenericParamKind,
     }
 );
 
-impl NodeConfig for GenericParam {}
+impl NodeConfig for GenericParam {
+    fn key(&self) -> Option<String> {
+        Some(if self.is_value() {
+            sources::variable_key(&self.ident)
+        } else {
+            sources::type_key(&self.ident)
+        })
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        if self.is_value() {
+            Some(NodeSource {
+                node: NodeRef::Type(self.bound_type(index)?),
+                generic_args: vec![],
+            })
+        } else {
+            None
+        }
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        let Some(bound) = self.kind.as_bound() else {
+            return;
+        };
+        if self.is_value() || self.interface(ctx.index).is_some() {
+            return;
+        }
+        if self.bound_type(ctx.index).is_some() {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                bound,
+                "unsupported generic parameter type",
+                Some("supported types are `u32`, `i32` and `bool`"),
+                &[],
+            ));
+        } else {
+            let key = sources::interface_key(bound);
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                bound,
+                "undefined item",
+                Some(&format!("{key} is undefined")),
+                &[],
+            ));
+        }
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+}
+
+impl TypeItem for GenericParam {
+    fn is_native(&self) -> bool {
+        false
+    }
+
+    fn name(&self, _index: &NodeIndex, _generic_args: &GenericArgs<'_>) -> String {
+        self.ident.slice.clone()
+    }
+
+    fn is_same_item(&self, other: &dyn TypeItem, _index: &NodeIndex) -> Option<bool> {
+        Some(self.id == other.id)
+    }
+
+    fn fields(&self) -> Vec<&StructField> {
+        vec![]
+    }
+
+    fn field(&self, _field_name: &str) -> Option<&StructField> {
+        None
+    }
+
+    // coverage: off (generic parameters are always specialized before being used)
+    fn size(&self, _index: &NodeIndex, _generic_args: &GenericArgs<'_>) -> u32 {
+        unreachable!("generic parameters have no size")
+    }
+
+    fn alignment(&self, _index: &NodeIndex, _generic_args: &GenericArgs<'_>) -> u32 {
+        unreachable!("generic parameters have no alignment")
+    }
+
+    fn transpiled_name(&self, _index: &NodeIndex, _generic_args: &GenericArgs<'_>) -> String {
+        unreachable!("generic parameters are not transpilable")
+    }
+
+    fn transpiled_field_name(&self, _field_name: &str) -> String {
+        unreachable!("generic parameters have no field")
+    }
+    // coverage: on
+}
+
+impl GenericParam {
+    pub(crate) fn is_value(&self) -> bool {
+        self.kind
+            .as_bound()
+            .is_some_and(|bound| GENERIC_VALUE_TYPES.contains(&bound.slice.as_str()))
+    }
+
+    /// Returns the interface the parameter is constrained with, if any.
+    pub(crate) fn interface<'a>(&self, index: &'a NodeIndex) -> Option<&'a InterfaceItem> {
+        let bound = self.kind.as_bound()?;
+        if self.is_value() {
+            return None;
+        }
+        let key = sources::interface_key(bound);
+        let interface = index.search(self, &key, sources::interface_criteria())?;
+        (interface as &dyn Any).downcast_ref::<InterfaceItem>()
+    }
+
+    fn bound_type<'a>(&self, index: &'a NodeIndex) -> Option<&'a dyn TypeItem> {
+        let key = sources::type_key(self.kind.as_bound()?);
+        Some(to_item(index.search(
+            self,
+            &key,
+            sources::type_criteria(),
+        )?))
+    }
+
+    pub(crate) fn position(&self, index: &NodeIndex) -> Option<usize> {
+        let item = self
+            .parent_ids
+            .iter()
+            .rev()
+            .find_map(|&parent_id| index.node(parent_id))?;
+        item.generic_params()
+            .iter()
+            .position(|param| param.id == self.id)
+    }
+}
+
+choice!(
+    enum GenericParamKind {
+        Type(TypeKeyword),
+        Bound(Ident),
+    }
+);
 
 sequence!(
     #[allow(unused_mut)]
@@ -392,10 +544,14 @@ impl NodeConfig for StructField {
 }
 
 impl StructField {
-    fn type_item<'a>(&self, index: &'a NodeIndex) -> &'a dyn TypeItem {
-        self.type_
-            .item(index)
+    pub(crate) fn specialized_type<'a>(
+        &'a self,
+        index: &'a NodeIndex,
+        generic_args: &GenericArgs<'a>,
+    ) -> NodeSource<'a> {
+        self.type_(index)
             .expect("internal error: invalid field type")
+            .specialized(generic_args, index)
     }
 }
 
@@ -414,26 +570,73 @@ pub(crate) fn to_item(node: &dyn Node) -> &dyn TypeItem {
         type_
     } else if let Some(type_) = (node as &dyn Any).downcast_ref::<StructItem>() {
         type_
+    } else if let Some(type_) = (node as &dyn Any).downcast_ref::<EnumItem>() {
+        type_
+    } else if let Some(type_) = (node as &dyn Any).downcast_ref::<GenericParam>() {
+        type_
     } else {
         unreachable!("unknown type item")
     }
 }
 
-fn field_offset(fields: &[&StructField], index: &NodeIndex) -> u32 {
-    if fields.len() == 1 {
+pub(crate) fn transpiled_name(
+    id: u32,
+    index: &NodeIndex,
+    generic_args: &GenericArgs<'_>,
+) -> String {
+    let mut name = format!("_{id}");
+    for arg in generic_args.iter().flatten() {
+        name += "_";
+        name += &arg.transpiled_type_name(index);
+    }
+    name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_")
+}
+
+pub(crate) fn name(ident: &Ident, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+    let ident_name = ident.slice.clone();
+    let generic_names = generic_args
+        .iter()
+        .map(|type_| {
+            type_.as_ref().map_or_else(
+                || UNKNOWN_TYPE.into(),
+                |type_| type_.name_or_no_return(index),
+            )
+        })
+        .join(", ");
+    if generic_names.is_empty() {
+        ident_name
+    } else {
+        format!("{ident_name}<{generic_names}>")
+    }
+}
+
+pub(crate) fn size(type_: &NodeSource<'_>, index: &NodeIndex) -> u32 {
+    type_
+        .type_item()
+        .expect("internal error: invalid type")
+        .size(index, &type_.generic_args)
+}
+
+pub(crate) fn alignment(type_: &NodeSource<'_>, index: &NodeIndex) -> u32 {
+    type_
+        .type_item()
+        .expect("internal error: invalid type")
+        .alignment(index, &type_.generic_args)
+}
+
+pub(crate) fn field_offset(field_types: &[NodeSource<'_>], index: &NodeIndex) -> u32 {
+    if field_types.len() == 1 {
         0
     } else {
-        let last_field_type = fields[fields.len() - 1].type_item(index);
-        let before_last_field_type = fields[fields.len() - 2].type_item(index);
-        let last_field_alignment = last_field_type.alignment(index);
-        let before_last_field_size = before_last_field_type.size(index);
+        let last_field_alignment = alignment(&field_types[field_types.len() - 1], index);
+        let before_last_field_size = size(&field_types[field_types.len() - 2], index);
         round_up(
             last_field_alignment,
-            field_offset(&fields[..fields.len() - 1], index) + before_last_field_size,
+            field_offset(&field_types[..field_types.len() - 1], index) + before_last_field_size,
         )
     }
 }
 
-fn round_up(k: u32, n: u32) -> u32 {
+pub(crate) fn round_up(k: u32, n: u32) -> u32 {
     n.div_ceil(k) * k
 }
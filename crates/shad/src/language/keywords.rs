@@ -1,39 +1,93 @@
 use crate::compilation::node::keyword;
 
 pub(crate) const RESERVED_KEYWORDS: &[&str] = &[
-    "alignof", "buf", "const", "false", "fn", "import", "init", "native", "prio", "pub", "ref",
-    "run", "return", "sizeof", "struct", "var", "true", "type",
+    "alignof",
+    "break",
+    "buf",
+    "const",
+    "continue",
+    "else",
+    "enum",
+    "every",
+    "false",
+    "fn",
+    "for",
+    "if",
+    "import",
+    "in",
+    "indirect",
+    "init",
+    "interface",
+    "lenof",
+    "loop",
+    "match",
+    "native",
+    "prio",
+    "pub",
+    "ref",
+    "run",
+    "return",
+    "shared",
+    "sizeof",
+    "struct",
+    "var",
+    "true",
+    "type",
+    "while",
 ];
 
 keyword!(AlignofKeyword, "alignof");
+keyword!(BreakKeyword, "break");
 keyword!(BufKeyword, "buf");
 keyword!(ConstKeyword, "const");
+keyword!(ContinueKeyword, "continue");
+keyword!(ElseKeyword, "else");
+keyword!(EnumKeyword, "enum");
+keyword!(EveryKeyword, "every");
 keyword!(FalseKeyword, "false");
 keyword!(FnKeyword, "fn");
+keyword!(ForKeyword, "for");
+keyword!(IfKeyword, "if");
 keyword!(ImportKeyword, "import");
+keyword!(InKeyword, "in");
+keyword!(IndirectKeyword, "indirect");
 keyword!(InitKeyword, "init");
+keyword!(InterfaceKeyword, "interface");
+keyword!(LenofKeyword, "lenof");
+keyword!(LoopKeyword, "loop");
+keyword!(MatchKeyword, "match");
 keyword!(NativeKeyword, "native");
 keyword!(PrioKeyword, "prio");
 keyword!(PubKeyword, "pub");
 keyword!(RefKeyword, "ref");
 keyword!(RunKeyword, "run");
 keyword!(ReturnKeyword, "return");
+keyword!(SharedKeyword, "shared");
 keyword!(SizeofKeyword, "sizeof");
 keyword!(StructKeyword, "struct");
 keyword!(VarKeyword, "var");
 keyword!(TrueKeyword, "true");
 keyword!(TypeKeyword, "type");
+keyword!(WhileKeyword, "while");
 
+keyword!(AmpersandSymbol, "&");
 keyword!(AndSymbol, "&&");
 keyword!(ArrowSymbol, "->");
+keyword!(CaretSymbol, "^");
 keyword!(CommaSymbol, ",");
 keyword!(CloseAngleBracketSymbol, ">");
 keyword!(CloseCurlyBracketSymbol, "}");
 keyword!(CloseParenthesisSymbol, ")");
+keyword!(CloseSquareBracketSymbol, "]");
 keyword!(ColonSymbol, ":");
+keyword!(DoubleCloseAngleBracketSymbol, ">>");
+keyword!(DoubleColonSymbol, "::");
+keyword!(DoubleDotSymbol, "..");
 keyword!(DoubleEqSymbol, "==");
+keyword!(DoubleOpenAngleBracketSymbol, "<<");
 keyword!(DotSymbol, ".");
 keyword!(EqSymbol, "=");
+keyword!(FatArrowSymbol, "=>");
 keyword!(ExclamationSymbol, "!");
 keyword!(GreaterEqSymbol, ">=");
 keyword!(HyphenSymbol, "-");
@@ -42,10 +96,13 @@ keyword!(NotEqSymbol, "!=");
 keyword!(OpenAngleBracketSymbol, "<");
 keyword!(OpenCurlyBracketSymbol, "{");
 keyword!(OpenParenthesisSymbol, "(");
+keyword!(OpenSquareBracketSymbol, "[");
 keyword!(OrSymbol, "||");
 keyword!(PercentSymbol, "%");
+keyword!(PipeSymbol, "|");
 keyword!(PlusSymbol, "+");
 keyword!(SlashSymbol, "/");
 keyword!(StarSymbol, "*");
 keyword!(SemicolonSymbol, ";");
 keyword!(TildeSymbol, "~");
+keyword!(UnderscoreSymbol, "_");
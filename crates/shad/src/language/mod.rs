@@ -3,6 +3,7 @@ pub(crate) mod expressions;
 pub(crate) mod items;
 pub(crate) mod keywords;
 pub(crate) mod patterns;
+mod races;
 mod sources;
 pub(crate) mod statements;
 mod transformations;
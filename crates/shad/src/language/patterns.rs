@@ -27,6 +27,24 @@ pattern!(
 
 impl NodeConfig for Ident {}
 
+pattern!(
+    TupleFieldIndex,
+    "tuple field index",
+    RESERVED_KEYWORDS,
+    [
+        (1, 1, INCLUDE('0'..='9')),
+        (0, usize::MAX, INCLUDE('0'..='9')),
+    ],
+);
+
+impl NodeConfig for TupleFieldIndex {}
+
+impl TupleFieldIndex {
+    pub(crate) fn position(&self) -> Option<usize> {
+        self.slice.parse().ok()
+    }
+}
+
 pattern!(
     F32Literal,
     "`f32` literal",
@@ -0,0 +1,564 @@
+use crate::compilation::constant::{ConstantContext, ConstantData, ConstantValue};
+use crate::compilation::index::NodeIndex;
+use crate::compilation::node::{Node, NodeConfig};
+use crate::compilation::validation::ValidationContext;
+use crate::language::expressions::binary::{BinaryOperator, MaybeBinaryExpr};
+use crate::language::expressions::chain::{
+    ChainExpr, ChainPrefix, ChainSuffix, TransformedChainExpr,
+};
+use crate::language::expressions::simple::{ParenthesizedExpr, VarIdentExpr};
+use crate::language::items::block::Block;
+use crate::language::items::buffer::BufferItem;
+use crate::language::items::compute::{Dispatch, InvocationIndex, WORKGROUP_SIZE};
+use crate::language::items::fn_;
+use crate::language::items::fn_::FnItem;
+use crate::language::statements::{
+    ElseBody, IfStmt, LocalRefDefStmt, LocalVarDefStmt, SharedDefStmt, Stmt,
+};
+use crate::language::type_ref;
+use crate::ValidationError;
+use itertools::Itertools;
+use std::any::Any;
+use std::collections::HashSet;
+use std::iter;
+
+/// Checks that invocations of a dispatched block never write the same location of a buffer or of
+/// a `shared` variable.
+///
+/// A write is considered safe only when the written location is indexed by a value unique to each
+/// invocation sharing the variable, when the write is atomic, or when the write is run by a single
+/// invocation because of an `if <invocation index> == <constant>` condition.
+///
+/// The invocation index is unique to each invocation. The local index
+/// `<invocation index> % WORKGROUP_SIZE` is unique to each invocation of a workgroup, so it can
+/// index `shared` variables, or guard them with an `if <local index> == <constant>` condition.
+/// Under such a condition, buffers can be indexed by the workgroup index
+/// `<invocation index> / WORKGROUP_SIZE`.
+pub(crate) fn check_data_races(
+    dispatch: Option<&Dispatch>,
+    block: &Block,
+    ctx: &mut ValidationContext<'_>,
+) {
+    let Some(dispatch) = dispatch.filter(|dispatch| dispatch.is_parallel(ctx.index)) else {
+        return;
+    };
+    let writes_ctx = WritesContext::new(ctx.index, dispatch.invocation_index(), block);
+    let mut writes = vec![];
+    writes_ctx.block_writes(block, WriteScope::AllInvocations, &mut writes);
+    for (write_position, write) in writes.iter().enumerate() {
+        let name = write.var.name();
+        if write.scope == WriteScope::AllInvocations {
+            let mut secondary =
+                vec![(&*dispatch.count as &dyn Node, "invocations run in parallel")];
+            if let Some(fn_write) = write.fn_write {
+                secondary.push((fn_write, "buffer written here"));
+            }
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                write.node,
+                "data race between invocations",
+                Some(&format!(
+                    "same location of `{name}` possibly written by several invocations"
+                )),
+                &secondary,
+            ));
+        } else if let Some(other_write) = writes[..write_position].iter().find(|other_write| {
+            other_write.var.id() == write.var.id()
+                && other_write.scope != WriteScope::AllInvocations
+                && other_write.scope.is_conflicting(write.scope)
+        }) {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                write.node,
+                "data race between invocations",
+                Some(&format!(
+                    "same location of `{name}` possibly written by another invocation"
+                )),
+                &[(other_write.node, "conflicting write")],
+            ));
+        }
+    }
+}
+
+struct WritesContext<'a> {
+    index: &'a NodeIndex,
+    invocation_index: Option<&'a InvocationIndex>,
+    // Local variables assigned after their definition cannot be used to derive invocation values.
+    mutated_var_ids: HashSet<u32>,
+}
+
+impl<'a> WritesContext<'a> {
+    fn new(
+        index: &'a NodeIndex,
+        invocation_index: Option<&'a InvocationIndex>,
+        block: &'a Block,
+    ) -> Self {
+        let mut ctx = Self {
+            index,
+            invocation_index,
+            mutated_var_ids: HashSet::new(),
+        };
+        ctx.mutated_var_ids = block
+            .nested_stmts()
+            .into_iter()
+            .flat_map(|stmt| direct_write_targets(stmt, index))
+            .filter_map(|(_, target)| ctx.written_root(target))
+            .map(|(source, _)| source.id)
+            .collect();
+        ctx
+    }
+
+    fn block_writes(&self, block: &'a Block, scope: WriteScope, writes: &mut Vec<VarWrite<'a>>) {
+        for stmt in block.statements.iter() {
+            for (node, target) in direct_write_targets(stmt, self.index) {
+                if let Some(write) = self.write(node, target, scope) {
+                    writes.push(write);
+                }
+            }
+            if let Some(if_) = stmt.as_if() {
+                self.if_writes(if_, scope, writes);
+            } else {
+                if let Some(header) = stmt.header() {
+                    self.fn_writes(header, scope, writes);
+                } else if stmt.nested_blocks().is_empty() {
+                    self.fn_writes(&**stmt, scope, writes);
+                }
+                for nested_block in stmt.nested_blocks() {
+                    self.block_writes(nested_block, scope, writes);
+                }
+            }
+        }
+    }
+
+    fn if_writes(&self, if_: &'a IfStmt, scope: WriteScope, writes: &mut Vec<VarWrite<'a>>) {
+        self.fn_writes(&*if_.condition, scope, writes);
+        let block_scope = match (scope, self.guarded_invocation(&if_.condition.expr)) {
+            (WriteScope::AllInvocations, Some(guarded_scope)) => guarded_scope,
+            _ => scope,
+        };
+        self.block_writes(&if_.block, block_scope, writes);
+        for else_ in if_.else_.iter() {
+            match &*else_.body {
+                ElseBody::If(else_if) => self.if_writes(else_if, scope, writes),
+                ElseBody::Block(else_block) => self.block_writes(else_block, scope, writes),
+            }
+        }
+    }
+
+    // Buffers written by a function are never indexed by the invocation index.
+    fn fn_writes(&self, node: &'a dyn Node, scope: WriteScope, writes: &mut Vec<VarWrite<'a>>) {
+        let fns = node
+            .direct_nested_sources(self.index)
+            .into_iter()
+            .filter_map(|source| (source.node() as &dyn Any).downcast_ref::<FnItem>())
+            .flat_map(|fn_| {
+                let nested_fns = fn_
+                    .nested_sources(self.index)
+                    .into_iter()
+                    .filter_map(|source| (source.node() as &dyn Any).downcast_ref::<FnItem>());
+                iter::once(fn_).chain(nested_fns)
+            })
+            .unique_by(|fn_| fn_.id);
+        for fn_ in fns {
+            for stmt in fn_.body.nested_stmts() {
+                for (fn_node, target) in direct_write_targets(stmt, self.index) {
+                    if let Some((var, _)) = self.written_var(target) {
+                        writes.push(VarWrite {
+                            node,
+                            fn_write: Some(fn_node),
+                            var,
+                            scope: self.write_scope(var, &[], scope),
+                        });
+                    }
+                }
+            }
+        }
+    }
+
+    fn write(
+        &self,
+        node: &'a dyn Node,
+        target: WriteTarget<'a>,
+        scope: WriteScope,
+    ) -> Option<VarWrite<'a>> {
+        let (var, indexes) = self.written_var(target)?;
+        Some(VarWrite {
+            node,
+            fn_write: None,
+            var,
+            scope: self.write_scope(var, &indexes, scope),
+        })
+    }
+
+    // Returns the scope of a write to `var` with the given indexes, in a block run in `scope`.
+    fn write_scope(
+        &self,
+        var: WrittenVar<'a>,
+        indexes: &[Option<&'a MaybeBinaryExpr>],
+        scope: WriteScope,
+    ) -> WriteScope {
+        for (level, index) in indexes.iter().enumerate() {
+            let Some(value) = index.and_then(|index| self.invocation_value(index)) else {
+                continue;
+            };
+            match (value, var, scope) {
+                (InvocationValue::Index, _, _)
+                | (InvocationValue::LocalIndex, WrittenVar::Shared(_), _) => {
+                    return WriteScope::Indexed(level, value);
+                }
+                (
+                    InvocationValue::WorkgroupIndex,
+                    WrittenVar::Buffer(_),
+                    WriteScope::SingleLocalInvocation(local_index),
+                ) => return WriteScope::WorkgroupIndexed(level, local_index),
+                _ => {}
+            }
+        }
+        match (var, scope) {
+            (WrittenVar::Buffer(_), WriteScope::SingleLocalInvocation(_)) => {
+                WriteScope::AllInvocations
+            }
+            _ => scope,
+        }
+    }
+
+    // Returns the written variable and the index of each level, from the variable to the location.
+    //
+    // Field accesses are index levels without index.
+    fn written_var(
+        &self,
+        target: WriteTarget<'a>,
+    ) -> Option<(WrittenVar<'a>, Vec<Option<&'a MaybeBinaryExpr>>)> {
+        if target
+            .node()
+            .type_(self.index)
+            .is_some_and(|type_| type_ref::is_atomic(&type_))
+        {
+            return None;
+        }
+        let (source, indexes) = self.written_root(target)?;
+        let source = source as &dyn Any;
+        let var = if let Some(buffer) = source.downcast_ref::<BufferItem>() {
+            WrittenVar::Buffer(buffer)
+        } else {
+            WrittenVar::Shared(source.downcast_ref::<SharedDefStmt>()?)
+        };
+        Some((var, indexes))
+    }
+
+    // Returns the written variable definition, following `ref` variables.
+    fn written_root(
+        &self,
+        target: WriteTarget<'a>,
+    ) -> Option<(&'a dyn Node, Vec<Option<&'a MaybeBinaryExpr>>)> {
+        let mut target = target;
+        let mut indexes = vec![];
+        loop {
+            if let Some(chain) = target.as_chain() {
+                match chain.suffix.iter().next().map(|suffix| &**suffix) {
+                    Some(ChainSuffix::Index(suffix)) => indexes.push(Some(&*suffix.index)),
+                    Some(ChainSuffix::StructField(_) | ChainSuffix::TupleField(_)) => {
+                        indexes.push(None);
+                    }
+                    None => {}
+                    Some(ChainSuffix::FnCall(_)) => return None,
+                }
+                target = WriteTarget::Expr(&chain.expr);
+            } else {
+                let source = target.as_var_ident()?.source(self.index)?.node();
+                if let Some(ref_def) = (source as &dyn Any).downcast_ref::<LocalRefDefStmt>() {
+                    target = WriteTarget::Expr(&ref_def.expr);
+                } else {
+                    indexes.reverse();
+                    return Some((source, indexes));
+                }
+            }
+        }
+    }
+
+    // Returns the scope of the block of an `if <invocation value> == <constant>`.
+    fn guarded_invocation(&self, condition: &MaybeBinaryExpr) -> Option<WriteScope> {
+        let MaybeBinaryExpr::Transformed(condition) = condition else {
+            return None;
+        };
+        if !matches!(&*condition.operator, BinaryOperator::Eq(_)) {
+            return None;
+        }
+        let (invocation_value, value) =
+            if let Some(invocation_value) = self.invocation_value(&condition.left) {
+                (invocation_value, &condition.right)
+            } else {
+                (self.invocation_value(&condition.right)?, &condition.left)
+            };
+        let value = self.constant_u32(value)?;
+        match invocation_value {
+            InvocationValue::Index => Some(WriteScope::SingleInvocation(value)),
+            InvocationValue::LocalIndex => Some(WriteScope::SingleLocalInvocation(value)),
+            InvocationValue::WorkgroupIndex => None,
+        }
+    }
+
+    fn invocation_value(&self, expr: &MaybeBinaryExpr) -> Option<InvocationValue> {
+        if let Some(var) = expr.as_var_ident() {
+            let source = var.source(self.index)?.node();
+            if self
+                .invocation_index
+                .is_some_and(|invocation_index| source.id == invocation_index.id)
+            {
+                return Some(InvocationValue::Index);
+            }
+            let var_def = (source as &dyn Any).downcast_ref::<LocalVarDefStmt>()?;
+            return if self.mutated_var_ids.contains(&var_def.id) {
+                None
+            } else {
+                self.invocation_value(&var_def.expr)
+            };
+        }
+        let MaybeBinaryExpr::Transformed(expr) = expr else {
+            return None;
+        };
+        let value = if matches!(&*expr.operator, BinaryOperator::Mod(_)) {
+            InvocationValue::LocalIndex
+        } else if matches!(&*expr.operator, BinaryOperator::Div(_)) {
+            InvocationValue::WorkgroupIndex
+        } else {
+            return None;
+        };
+        (self.invocation_value(&expr.left) == Some(InvocationValue::Index)
+            && self.constant_u32(&expr.right) == Some(WORKGROUP_SIZE))
+        .then_some(value)
+    }
+
+    fn constant_u32(&self, expr: &MaybeBinaryExpr) -> Option<u32> {
+        match expr.evaluate_constant(&mut ConstantContext::new(self.index)) {
+            Some(ConstantValue {
+                data: ConstantData::U32(value),
+                ..
+            }) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+struct VarWrite<'a> {
+    node: &'a dyn Node,
+    fn_write: Option<&'a dyn Node>,
+    var: WrittenVar<'a>,
+    scope: WriteScope,
+}
+
+#[derive(Clone, Copy)]
+enum WrittenVar<'a> {
+    Buffer(&'a BufferItem),
+    Shared(&'a SharedDefStmt),
+}
+
+impl<'a> WrittenVar<'a> {
+    fn id(self) -> u32 {
+        match self {
+            Self::Buffer(buffer) => buffer.id,
+            Self::Shared(shared) => shared.id,
+        }
+    }
+
+    fn name(self) -> &'a str {
+        match self {
+            Self::Buffer(buffer) => &buffer.ident.slice,
+            Self::Shared(shared) => &shared.ident.slice,
+        }
+    }
+}
+
+// A value derived from the invocation index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InvocationValue {
+    Index,
+    // The index of the invocation in its workgroup.
+    LocalIndex,
+    WorkgroupIndex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WriteScope {
+    // The location is indexed by an invocation value at the stored index level.
+    Indexed(usize, InvocationValue),
+    // The location is indexed by the workgroup index at the stored index level, and written only
+    // by the invocation with the stored local index.
+    WorkgroupIndexed(usize, u32),
+    SingleInvocation(u32),
+    SingleLocalInvocation(u32),
+    AllInvocations,
+}
+
+impl WriteScope {
+    fn is_conflicting(self, other: Self) -> bool {
+        match (self, other) {
+            (Self::Indexed(..), Self::Indexed(..))
+            | (Self::WorkgroupIndexed(..), Self::WorkgroupIndexed(..))
+            | (Self::SingleInvocation(_), Self::SingleInvocation(_))
+            | (Self::SingleLocalInvocation(_), Self::SingleLocalInvocation(_)) => self != other,
+            _ => true,
+        }
+    }
+}
+
+// A written location, which is the operand of a unary operator for `ref` operator parameters.
+#[derive(Clone, Copy)]
+enum WriteTarget<'a> {
+    Expr(&'a MaybeBinaryExpr),
+    Chain(&'a ChainExpr),
+}
+
+impl<'a> WriteTarget<'a> {
+    fn node(self) -> &'a dyn Node {
+        match self {
+            Self::Expr(expr) => expr,
+            Self::Chain(expr) => expr,
+        }
+    }
+
+    fn as_chain(self) -> Option<&'a TransformedChainExpr> {
+        match self {
+            Self::Expr(expr) => expr.as_chain(),
+            Self::Chain(expr) => expr.as_transformed(),
+        }
+    }
+
+    fn as_var_ident(self) -> Option<&'a VarIdentExpr> {
+        match self {
+            Self::Expr(expr) => expr.as_var_ident(),
+            Self::Chain(expr) => expr.as_var_ident(),
+        }
+    }
+}
+
+// Returns the locations written by assignments and by non-atomic `ref` arguments.
+fn direct_write_targets<'a>(
+    stmt: &'a Stmt,
+    index: &'a NodeIndex,
+) -> Vec<(&'a dyn Node, WriteTarget<'a>)> {
+    let assignment_targets = stmt.as_assignment().map(|assignment| {
+        (
+            &*assignment.left as &dyn Node,
+            WriteTarget::Expr(&assignment.left),
+        )
+    });
+    let mut targets: Vec<_> = assignment_targets.into_iter().collect();
+    for expr in stmt.exprs() {
+        expr_ref_args(expr, index, &mut targets);
+    }
+    targets
+}
+
+// Calls are searched in the whole expression, including in the arguments of other calls.
+fn expr_ref_args<'a>(
+    expr: &'a MaybeBinaryExpr,
+    index: &'a NodeIndex,
+    targets: &mut Vec<(&'a dyn Node, WriteTarget<'a>)>,
+) {
+    match expr {
+        MaybeBinaryExpr::Parsed(expr) => {
+            chain_ref_args(&expr.left, index, targets);
+            for right in expr.right.iter() {
+                chain_ref_args(&right.operand, index, targets);
+            }
+        }
+        MaybeBinaryExpr::Transformed(expr) => {
+            let args = [
+                WriteTarget::Expr(&expr.left),
+                WriteTarget::Expr(&expr.right),
+            ];
+            call_ref_args(&**expr, args.into_iter(), index, targets);
+            expr_ref_args(&expr.left, index, targets);
+            expr_ref_args(&expr.right, index, targets);
+        }
+    }
+}
+
+fn chain_ref_args<'a>(
+    expr: &'a ChainExpr,
+    index: &'a NodeIndex,
+    targets: &mut Vec<(&'a dyn Node, WriteTarget<'a>)>,
+) {
+    let expr = match expr {
+        ChainExpr::Parsed(expr) => return prefix_ref_args(&expr.expr, index, targets),
+        ChainExpr::Transformed(expr) => expr,
+    };
+    match expr.suffix.iter().next().map(|suffix| &**suffix) {
+        Some(ChainSuffix::FnCall(suffix)) => {
+            let args = expr.args(suffix).map(WriteTarget::Expr);
+            call_ref_args(&**expr, args, index, targets);
+            for arg in expr.args(suffix) {
+                expr_ref_args(arg, index, targets);
+            }
+        }
+        Some(ChainSuffix::Index(suffix)) => {
+            expr_ref_args(&expr.expr, index, targets);
+            expr_ref_args(&suffix.index, index, targets);
+        }
+        Some(ChainSuffix::StructField(_) | ChainSuffix::TupleField(_)) | None => {
+            expr_ref_args(&expr.expr, index, targets);
+        }
+    }
+}
+
+fn prefix_ref_args<'a>(
+    prefix: &'a ChainPrefix,
+    index: &'a NodeIndex,
+    targets: &mut Vec<(&'a dyn Node, WriteTarget<'a>)>,
+) {
+    let nested_exprs: Vec<&MaybeBinaryExpr> = match prefix {
+        ChainPrefix::FnCall(call) => {
+            call_ref_args(&**call, call.args().map(WriteTarget::Expr), index, targets);
+            call.args().collect()
+        }
+        ChainPrefix::Unary(expr) => {
+            let args = iter::once(WriteTarget::Chain(&expr.operand));
+            call_ref_args(&**expr, args, index, targets);
+            chain_ref_args(&expr.operand, index, targets);
+            vec![]
+        }
+        ChainPrefix::Constructor(expr) => expr.args().map(|arg| &*arg.expr).collect(),
+        ChainPrefix::EnumVariant(expr) => {
+            expr.payload.iter().map(|payload| &*payload.value).collect()
+        }
+        ChainPrefix::Parenthesized(expr) => match &**expr {
+            ParenthesizedExpr::Parsed(expr) => iter::once(&*expr.expr)
+                .chain(expr.other_items.iter().map(|other| &*other.item))
+                .collect(),
+            ParenthesizedExpr::Transformed(expr) => expr.items().collect(),
+        },
+        ChainPrefix::Lenof(expr) => vec![&expr.expr],
+        ChainPrefix::True(_)
+        | ChainPrefix::False(_)
+        | ChainPrefix::F32(_)
+        | ChainPrefix::U32(_)
+        | ChainPrefix::I32(_)
+        | ChainPrefix::Var(_)
+        | ChainPrefix::TypeOperation(_) => vec![],
+    };
+    for expr in nested_exprs {
+        expr_ref_args(expr, index, targets);
+    }
+}
+
+fn call_ref_args<'a>(
+    call: &'a dyn Node,
+    args: impl Iterator<Item = WriteTarget<'a>>,
+    index: &'a NodeIndex,
+    targets: &mut Vec<(&'a dyn Node, WriteTarget<'a>)>,
+) {
+    let Some(source) = call.source(index) else {
+        return;
+    };
+    let ref_args = args
+        .zip(fn_::signature(source.node()).params())
+        .filter(|(_, param)| {
+            param.is_ref(index) == Some(true)
+                && param
+                    .type_(index)
+                    .is_none_or(|type_| !type_ref::is_atomic(&type_))
+        })
+        .map(|(arg, _)| (arg.node(), arg));
+    targets.extend(ref_args);
+}
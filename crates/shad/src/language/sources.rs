@@ -2,13 +2,20 @@ use crate::compilation::index::NodeIndex;
 use crate::compilation::node::{NodeConfig, NodeSource, NodeSourceSearchCriteria, Repeated};
 use crate::language::expressions::binary::MaybeBinaryExpr;
 use crate::language::items::buffer::BufferItem;
+use crate::language::items::compute::InvocationIndex;
 use crate::language::items::constant::ConstantItem;
+use crate::language::items::enum_::EnumItem;
 use crate::language::items::fn_::{FnItem, FnParam, FnParamGroup, NativeFnItem};
-use crate::language::items::type_::{NativeStructItem, StructItem};
+use crate::language::items::interface::InterfaceItem;
+use crate::language::items::type_::{GenericParam, NativeStructItem, StructItem};
 use crate::language::patterns::Ident;
-use crate::language::statements::{LocalRefDefStmt, LocalVarDefStmt};
+use crate::language::statements::{
+    EnumVariantPattern, ForRange, LocalRefDefStmt, LocalTupleDefStmt, LocalVarDefStmt,
+    SharedDefStmt,
+};
 use itertools::Itertools;
 use std::any::TypeId;
+use std::iter;
 
 pub(crate) fn variable_key(ident: &Ident) -> String {
     let name = &ident.slice;
@@ -20,6 +27,16 @@ pub(crate) fn type_key(ident: &Ident) -> String {
     format!("`{name}` type")
 }
 
+pub(crate) fn variant_key(ident: &Ident, type_name: &str) -> String {
+    let name = &ident.slice;
+    format!("`{name}` variant of `{type_name}`")
+}
+
+pub(crate) fn interface_key(ident: &Ident) -> String {
+    let name = &ident.slice;
+    format!("`{name}` interface")
+}
+
 pub(crate) fn fn_key_from_params(ident: &Ident, params: &Repeated<FnParamGroup, 0, 1>) -> String {
     let name = &ident.slice;
     let params = params
@@ -30,6 +47,11 @@ pub(crate) fn fn_key_from_params(ident: &Ident, params: &Repeated<FnParamGroup,
     format!("`{name}({params})` function")
 }
 
+pub(crate) fn generic_fn_key(name: &str, param_count: usize) -> String {
+    let params = iter::repeat_n("_", param_count).join(", ");
+    format!("`{name}({params})` generic function")
+}
+
 pub(crate) fn fn_key_from_args<'a>(
     ident: &Ident,
     args: impl Iterator<Item = &'a MaybeBinaryExpr>,
@@ -37,7 +59,7 @@ pub(crate) fn fn_key_from_args<'a>(
 ) -> Option<String> {
     let name = &ident.slice;
     let arg_types = args
-        .map(|arg| arg.type_(index).map(|type_| type_.name_or_no_return()))
+        .map(|arg| arg.type_(index).map(|type_| type_.name_or_no_return(index)))
         .collect::<Option<Vec<_>>>()?
         .join(", ");
     Some(format!("`{name}({arg_types})` function"))
@@ -46,10 +68,11 @@ pub(crate) fn fn_key_from_args<'a>(
 pub(crate) fn fn_key_from_operator<'a>(
     name: &str,
     arg_types: impl IntoIterator<Item = NodeSource<'a>>,
+    index: &NodeIndex,
 ) -> String {
     let arg_types = arg_types
         .into_iter()
-        .map(|type_| type_.name_or_no_return())
+        .map(|type_| type_.name_or_no_return(index))
         .join(", ");
     format!("`{name}({arg_types})` function")
 }
@@ -61,15 +84,45 @@ pub(crate) fn variable_criteria() -> &'static [NodeSourceSearchCriteria] {
             can_be_after: false,
             common_parent_count: None,
         },
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<LocalTupleDefStmt>(),
+            can_be_after: false,
+            common_parent_count: None,
+        },
         NodeSourceSearchCriteria {
             node_type: || TypeId::of::<LocalRefDefStmt>(),
             can_be_after: false,
             common_parent_count: None,
         },
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<SharedDefStmt>(),
+            can_be_after: false,
+            common_parent_count: None,
+        },
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<ForRange>(),
+            can_be_after: false,
+            common_parent_count: None,
+        },
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<EnumVariantPattern>(),
+            can_be_after: false,
+            common_parent_count: None,
+        },
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<InvocationIndex>(),
+            can_be_after: false,
+            common_parent_count: Some(3),
+        },
         NodeSourceSearchCriteria {
             node_type: || TypeId::of::<FnParam>(),
             can_be_after: false,
-            common_parent_count: Some(2),
+            common_parent_count: Some(3),
+        },
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<GenericParam>(),
+            can_be_after: false,
+            common_parent_count: Some(3),
         },
         NodeSourceSearchCriteria {
             node_type: || TypeId::of::<BufferItem>(),
@@ -84,6 +137,21 @@ pub(crate) fn variable_criteria() -> &'static [NodeSourceSearchCriteria] {
     ]
 }
 
+pub(crate) fn generic_value_criteria() -> &'static [NodeSourceSearchCriteria] {
+    &[
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<GenericParam>(),
+            can_be_after: false,
+            common_parent_count: Some(3),
+        },
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<ConstantItem>(),
+            can_be_after: true,
+            common_parent_count: None,
+        },
+    ]
+}
+
 pub(crate) fn fn_criteria() -> &'static [NodeSourceSearchCriteria] {
     &[
         NodeSourceSearchCriteria {
@@ -111,5 +179,23 @@ pub(crate) fn type_criteria() -> &'static [NodeSourceSearchCriteria] {
             can_be_after: true,
             common_parent_count: None,
         },
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<EnumItem>(),
+            can_be_after: true,
+            common_parent_count: None,
+        },
+        NodeSourceSearchCriteria {
+            node_type: || TypeId::of::<GenericParam>(),
+            can_be_after: false,
+            common_parent_count: Some(3),
+        },
     ]
 }
+
+pub(crate) fn interface_criteria() -> &'static [NodeSourceSearchCriteria] {
+    &[NodeSourceSearchCriteria {
+        node_type: || TypeId::of::<InterfaceItem>(),
+        can_be_after: true,
+        common_parent_count: None,
+    }]
+}
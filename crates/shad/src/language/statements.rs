@@ -1,24 +1,169 @@
-use crate::compilation::constant::{ConstantContext, ConstantValue};
+use crate::compilation::constant::{
+    ConstantContext, ConstantData, ConstantInterruption, ConstantValue,
+};
 use crate::compilation::index::NodeIndex;
-use crate::compilation::node::{choice, sequence, GenericArgs, Node, NodeConfig, NodeSource};
+use crate::compilation::node::{
+    choice, sequence, GenericArgs, Node, NodeConfig, NodeRef, NodeSource, Repeated,
+};
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
-use crate::language::expressions::binary::MaybeBinaryExpr;
-use crate::language::keywords::{EqSymbol, RefKeyword, ReturnKeyword, SemicolonSymbol, VarKeyword};
-use crate::language::patterns::Ident;
-use crate::language::{sources, validations};
+use crate::language::expressions::binary::{BlockHeaderExpr, MaybeBinaryExpr};
+use crate::language::expressions::chain::{ChainExpr, TransformedChainExpr};
+use crate::language::expressions::simple;
+use crate::language::items::block::Block;
+use crate::language::items::constant;
+use crate::language::items::enum_;
+use crate::language::items::enum_::EnumItem;
+use crate::language::keywords::{
+    BreakKeyword, CloseCurlyBracketSymbol, CloseParenthesisSymbol, ColonSymbol, CommaSymbol,
+    ContinueKeyword, DoubleColonSymbol, DoubleDotSymbol, ElseKeyword, EqSymbol, FatArrowSymbol,
+    ForKeyword, IfKeyword, InKeyword, LoopKeyword, MatchKeyword, OpenCurlyBracketSymbol,
+    OpenParenthesisSymbol, PipeSymbol, RefKeyword, ReturnKeyword, SemicolonSymbol, SharedKeyword,
+    UnderscoreSymbol, VarKeyword, WhileKeyword,
+};
+use crate::language::patterns::{I32Literal, Ident, U32Literal};
+use crate::language::type_ref::{NamedType, Type};
+use crate::language::{sources, type_ref, validations};
 use crate::ValidationError;
+use itertools::Itertools;
+use std::any::Any;
+use std::ops::ControlFlow;
+use std::{iter, mem};
 
 choice!(
     enum Stmt {
+        LocalTupleDef(LocalTupleDefStmt),
         LocalVarDef(LocalVarDefStmt),
         LocalRefDef(LocalRefDefStmt),
+        SharedDef(SharedDefStmt),
         Assignment(AssignmentStmt),
         Expr(ExprStmt),
         Return(ReturnStmt),
+        If(IfStmt),
+        Match(MatchStmt),
+        For(ForStmt),
+        While(WhileStmt),
+        Loop(LoopStmt),
+        Break(BreakStmt),
+        Continue(ContinueStmt),
     }
 );
 
+impl Stmt {
+    pub(crate) fn is_returning(&self) -> bool {
+        match self {
+            Self::Return(_) => true,
+            Self::If(stmt) => stmt.is_returning(),
+            Self::Match(stmt) => stmt.is_returning(),
+            Self::Loop(stmt) => stmt.is_returning(),
+            Self::LocalTupleDef(_)
+            | Self::LocalVarDef(_)
+            | Self::LocalRefDef(_)
+            | Self::SharedDef(_)
+            | Self::Assignment(_)
+            | Self::Expr(_)
+            | Self::For(_)
+            | Self::While(_)
+            | Self::Break(_)
+            | Self::Continue(_) => false,
+        }
+    }
+
+    pub(crate) fn return_stmts(&self) -> Vec<&ReturnStmt> {
+        match self {
+            Self::Return(stmt) => vec![&**stmt],
+            Self::If(stmt) => stmt.return_stmts(),
+            Self::Match(stmt) => stmt.return_stmts(),
+            Self::For(stmt) => stmt.block.return_stmts(),
+            Self::While(stmt) => stmt.block.return_stmts(),
+            Self::Loop(stmt) => stmt.block.return_stmts(),
+            Self::LocalTupleDef(_)
+            | Self::LocalVarDef(_)
+            | Self::LocalRefDef(_)
+            | Self::SharedDef(_)
+            | Self::Assignment(_)
+            | Self::Expr(_)
+            | Self::Break(_)
+            | Self::Continue(_) => vec![],
+        }
+    }
+
+    // `break` and `continue` statements that are not nested in a loop.
+    pub(crate) fn loop_control_stmts(&self) -> Vec<&Self> {
+        match self {
+            Self::Break(_) | Self::Continue(_) => vec![self],
+            Self::If(stmt) => stmt.loop_control_stmts(),
+            Self::Match(stmt) => stmt.loop_control_stmts(),
+            Self::LocalTupleDef(_)
+            | Self::LocalVarDef(_)
+            | Self::LocalRefDef(_)
+            | Self::SharedDef(_)
+            | Self::Assignment(_)
+            | Self::Expr(_)
+            | Self::Return(_)
+            | Self::For(_)
+            | Self::While(_)
+            | Self::Loop(_) => vec![],
+        }
+    }
+
+    pub(crate) fn nested_blocks(&self) -> Vec<&Block> {
+        match self {
+            Self::If(stmt) => stmt.blocks(),
+            Self::Match(stmt) => stmt.arms.iter().map(|arm| &*arm.block).collect(),
+            Self::For(stmt) => vec![&stmt.block],
+            Self::While(stmt) => vec![&stmt.block],
+            Self::Loop(stmt) => vec![&stmt.block],
+            Self::LocalTupleDef(_)
+            | Self::LocalVarDef(_)
+            | Self::LocalRefDef(_)
+            | Self::SharedDef(_)
+            | Self::Assignment(_)
+            | Self::Expr(_)
+            | Self::Return(_)
+            | Self::Break(_)
+            | Self::Continue(_) => vec![],
+        }
+    }
+
+    /// Returns the expression evaluated before running the nested blocks, if any.
+    pub(crate) fn header(&self) -> Option<&dyn Node> {
+        match self {
+            Self::If(stmt) => Some(&*stmt.condition),
+            Self::Match(stmt) => Some(&*stmt.expr),
+            Self::For(stmt) => Some(&*stmt.range),
+            Self::While(stmt) => Some(&*stmt.condition),
+            Self::LocalTupleDef(_)
+            | Self::LocalVarDef(_)
+            | Self::LocalRefDef(_)
+            | Self::SharedDef(_)
+            | Self::Assignment(_)
+            | Self::Expr(_)
+            | Self::Return(_)
+            | Self::Loop(_)
+            | Self::Break(_)
+            | Self::Continue(_) => None,
+        }
+    }
+
+    /// Returns the expressions of the statement, excluding the ones of the nested blocks.
+    pub(crate) fn exprs(&self) -> Vec<&MaybeBinaryExpr> {
+        match self {
+            Self::LocalTupleDef(stmt) => vec![&stmt.expr],
+            Self::LocalVarDef(stmt) => vec![&stmt.expr],
+            Self::LocalRefDef(stmt) => vec![&stmt.expr],
+            Self::Assignment(stmt) => vec![&stmt.left, &stmt.right],
+            Self::Expr(stmt) => vec![&stmt.expr],
+            Self::Return(stmt) => vec![&stmt.expr],
+            Self::If(stmt) => stmt.conditions(),
+            Self::Match(stmt) => vec![&stmt.expr.expr],
+            Self::For(stmt) => vec![&stmt.range.start, &stmt.range.end.expr],
+            Self::While(stmt) => vec![&stmt.condition.expr],
+            Self::SharedDef(_) | Self::Loop(_) | Self::Break(_) | Self::Continue(_) => vec![],
+        }
+    }
+}
+
 sequence!(
     struct LocalVarDefStmt {
         var: VarKeyword,
@@ -41,6 +186,96 @@ impl NodeConfig for LocalVarDefStmt {
 
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
         validations::check_no_return_type(&*self.expr, ctx);
+        validations::check_copyable_expr(&*self.expr, ctx);
+    }
+
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.expr.invalid_constant(index)
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        let value = self.expr.evaluate_constant(ctx)?;
+        ctx.create_var(self.id, value);
+        None
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let var_name = if ctx.inline_state.is_inlined {
+            let id = ctx.next_node_id();
+            let var_name = format!("_{id}");
+            ctx.add_inline_mapping(self.id, &var_name);
+            var_name
+        } else {
+            format!("_{}", self.id)
+        };
+        let expr = self.expr.transpile(ctx, generic_args);
+        format!("var {var_name} = {expr};")
+    }
+}
+
+sequence!(
+    struct LocalTupleDefStmt {
+        var: VarKeyword,
+        names_start: OpenParenthesisSymbol,
+        #[force_error(true)]
+        first_name: Ident,
+        other_names: Repeated<LocalTupleOtherName, 1, { usize::MAX }>,
+        names_end: CloseParenthesisSymbol,
+        eq: EqSymbol,
+        expr: MaybeBinaryExpr,
+        semicolon: SemicolonSymbol,
+    }
+);
+
+impl NodeConfig for LocalTupleDefStmt {
+    fn keys(&self) -> Vec<String> {
+        self.names().map(sources::variable_key).collect()
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.expr.type_(index)
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        validations::check_no_return_type(&*self.expr, ctx);
+        if let Some(type_) = self.expr.type_(ctx.index) {
+            let name_count = self.names().count();
+            let is_valid = type_ref::tuple_item_types(&type_)
+                .is_some_and(|item_types| item_types.len() == name_count);
+            if !type_.is_no_return() && !is_valid {
+                let type_name = type_.name_or_no_return(ctx.index);
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    &*self.expr,
+                    "invalid expression type",
+                    Some(&format!(
+                        "expression type is `{type_name}` but expected type is a tuple with {name_count} items"
+                    )),
+                    &[],
+                ));
+            }
+        }
+        for name1 in self.names() {
+            for name2 in self.names() {
+                if name1.id < name2.id && name1.slice == name2.slice {
+                    ctx.errors.push(ValidationError::error(
+                        ctx,
+                        name2,
+                        "variable defined multiple times",
+                        Some("duplicated variable name"),
+                        &[(name1, "same variable name defined here")],
+                    ));
+                }
+            }
+        }
     }
 
     fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
@@ -75,6 +310,36 @@ impl NodeConfig for LocalVarDefStmt {
     }
 }
 
+impl LocalTupleDefStmt {
+    pub(crate) fn names(&self) -> impl Iterator<Item = &Ident> {
+        iter::once(&*self.first_name).chain(self.other_names.iter().map(|other| &*other.name))
+    }
+
+    pub(crate) fn var_position(&self, name: &str) -> Option<usize> {
+        self.names().position(|ident| ident.slice == name)
+    }
+
+    pub(crate) fn var_type<'a>(
+        &'a self,
+        name: &str,
+        index: &'a NodeIndex,
+    ) -> Option<NodeSource<'a>> {
+        type_ref::tuple_item_types(&self.expr.type_(index)?)?
+            .into_iter()
+            .nth(self.var_position(name)?)?
+    }
+}
+
+sequence!(
+    struct LocalTupleOtherName {
+        comma: CommaSymbol,
+        #[force_error(true)]
+        name: Ident,
+    }
+);
+
+impl NodeConfig for LocalTupleOtherName {}
+
 sequence!(
     struct LocalRefDefStmt {
         ref_: RefKeyword,
@@ -131,6 +396,52 @@ impl NodeConfig for LocalRefDefStmt {
     }
 }
 
+// A `shared` variable is transpiled as a `var<workgroup>` declaration by its compute item.
+sequence!(
+    struct SharedDefStmt {
+        shared: SharedKeyword,
+        #[force_error(true)]
+        ident: Ident,
+        colon: ColonSymbol,
+        type_: Type,
+        semicolon: SemicolonSymbol,
+    }
+);
+
+impl NodeConfig for SharedDefStmt {
+    fn key(&self) -> Option<String> {
+        Some(sources::variable_key(&self.ident))
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.type_.type_(index)
+    }
+
+    fn invalid_constant(&self, _index: &NodeIndex) -> Option<&dyn Node> {
+        Some(self)
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+
+    fn transpile(
+        &self,
+        _ctx: &mut TranspilationContext<'_>,
+        _generic_args: &GenericArgs<'_>,
+    ) -> String {
+        String::new()
+    }
+}
+
+impl SharedDefStmt {
+    pub(crate) fn transpile_declaration(&self, ctx: &mut TranspilationContext<'_>) -> String {
+        let id = self.id;
+        let type_ = self.type_.transpile(ctx, &vec![]);
+        format!("var<workgroup> _{id}: {type_};")
+    }
+}
+
 sequence!(
     struct AssignmentStmt {
         left: MaybeBinaryExpr,
@@ -143,7 +454,7 @@ sequence!(
 
 impl NodeConfig for AssignmentStmt {
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
-        if self.left.is_ref(ctx.index) == Some(false) {
+        if self.left.is_ref(ctx.index) == Some(false) && self.swizzle_target(ctx.index).is_none() {
             ctx.errors.push(ValidationError::error(
                 ctx,
                 &*self.left,
@@ -154,10 +465,24 @@ impl NodeConfig for AssignmentStmt {
         }
         validations::check_invalid_expr_type(&*self.left, &*self.right, false, ctx);
         validations::check_no_return_type(&*self.right, ctx);
+        validations::check_copyable_expr(&*self.right, ctx);
     }
 
-    fn invalid_constant(&self, _index: &NodeIndex) -> Option<&dyn Node> {
-        Some(self)
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        if self.local_var_def(index).is_some() {
+            self.right.invalid_constant(index)
+        } else {
+            Some(self)
+        }
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        let value = self.right.evaluate_constant(ctx)?;
+        let var_def = self
+            .local_var_def(ctx.index)
+            .expect("internal error: const assignment target is not a local variable");
+        ctx.update_var(var_def.id, value);
+        None
     }
 
     fn transpile(
@@ -165,12 +490,41 @@ impl NodeConfig for AssignmentStmt {
         ctx: &mut TranspilationContext<'_>,
         generic_args: &GenericArgs<'_>,
     ) -> String {
+        if let Some((target, swizzle)) = self.swizzle_target(ctx.index) {
+            // WGSL doesn't support assignment to swizzles, so components are assigned one by one.
+            let target = target.expr.transpile(ctx, generic_args);
+            let right = self.right.transpile(ctx, generic_args);
+            let value_id = ctx.next_node_id();
+            let assignments = swizzle
+                .chars()
+                .zip("xyzw".chars())
+                .map(|(target_component, value_component)| {
+                    format!("{target}.{target_component} = _{value_id}.{value_component};")
+                })
+                .join("\n");
+            return format!("var _{value_id} = {right};\n{assignments}");
+        }
         let left = self.left.transpile(ctx, generic_args);
         let right = self.right.transpile(ctx, generic_args);
         format!("{left} = {right};")
     }
 }
 
+impl AssignmentStmt {
+    // Only assignments to whole local variables are supported in `const` scopes.
+    fn local_var_def<'a>(&'a self, index: &'a NodeIndex) -> Option<&'a LocalVarDefStmt> {
+        let source = self.left.as_var_ident()?.source(index)?.node();
+        (source as &dyn Any).downcast_ref::<LocalVarDefStmt>()
+    }
+
+    fn swizzle_target(&self, index: &NodeIndex) -> Option<(&TransformedChainExpr, &str)> {
+        let chain = self.left.as_chain()?;
+        let swizzle = chain.swizzle(index)?;
+        let is_writable = chain.expr.is_ref(index) == Some(true) && swizzle.chars().all_unique();
+        is_writable.then_some((chain, swizzle))
+    }
+}
+
 sequence!(
     #[allow(unused_mut)]
     struct ExprStmt {
@@ -184,6 +538,10 @@ impl NodeConfig for ExprStmt {
         Some(self)
     }
 
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        validations::check_copyable_expr(&*self.expr, ctx);
+    }
+
     fn transpile(
         &self,
         ctx: &mut TranspilationContext<'_>,
@@ -199,6 +557,14 @@ impl NodeConfig for ExprStmt {
     }
 }
 
+impl ExprStmt {
+    pub(crate) fn is_barrier(&self, index: &NodeIndex) -> bool {
+        self.expr
+            .as_fn_call()
+            .is_some_and(|call| call.is_barrier(index))
+    }
+}
+
 sequence!(
     struct ReturnStmt {
         return_: ReturnKeyword,
@@ -242,9 +608,1017 @@ impl NodeConfig for ReturnStmt {
                 format!("var {var_name} = {expr};")
             }
         } else if let Some(var_id) = ctx.inline_state.return_var_id {
-            format!("_{var_id} = {expr};")
+            if let Some(flag_id) = ctx.inline_state.returned_flag_id {
+                format!("_{var_id} = {expr};\n_{flag_id} = true;\nbreak;")
+            } else {
+                format!("_{var_id} = {expr};\nbreak;")
+            }
         } else {
             format!("return {expr};")
         }
     }
 }
+
+sequence!(
+    struct IfStmt {
+        if_: IfKeyword,
+        #[force_error(true)]
+        condition: BlockHeaderExpr,
+        block: Block,
+        else_: Repeated<ElseBranch, 0, 1>,
+    }
+);
+
+impl NodeConfig for IfStmt {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        let bool_type = NodeSource {
+            node: NodeRef::Type(simple::bool_type(self, ctx.index)),
+            generic_args: vec![],
+        };
+        validations::check_invalid_const_expr_type(&bool_type, &*self.condition, ctx);
+    }
+
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.condition
+            .invalid_constant(index)
+            .or_else(|| self.block.invalid_constant(index))
+            .or_else(|| {
+                self.else_
+                    .iter()
+                    .find_map(|else_| else_.body.invalid_constant(index))
+            })
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        let condition = self.condition.evaluate_constant(ctx)?;
+        if matches!(condition.data, ConstantData::Bool(true)) {
+            self.block.evaluate_constant(ctx)
+        } else {
+            self.else_
+                .iter()
+                .next()
+                .and_then(|else_| else_.body.evaluate_constant(ctx))
+        }
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let condition = self.condition.transpile(ctx, generic_args);
+        let condition_stmts = mem::take(&mut ctx.generated_stmts);
+        let block = self.block.transpile(ctx, generic_args);
+        let else_ = self.else_.transpile(ctx, generic_args);
+        ctx.generated_stmts = condition_stmts;
+        format!("if bool({condition}) {{\n{block}\n}}{else_}")
+    }
+}
+
+impl IfStmt {
+    fn is_returning(&self) -> bool {
+        self.block.is_returning()
+            && self
+                .else_
+                .iter()
+                .next()
+                .is_some_and(|else_| else_.body.is_returning())
+    }
+
+    fn return_stmts(&self) -> Vec<&ReturnStmt> {
+        self.block
+            .return_stmts()
+            .into_iter()
+            .chain(
+                self.else_
+                    .iter()
+                    .flat_map(|else_| else_.body.return_stmts()),
+            )
+            .collect()
+    }
+
+    fn loop_control_stmts(&self) -> Vec<&Stmt> {
+        self.block
+            .loop_control_stmts()
+            .into_iter()
+            .chain(
+                self.else_
+                    .iter()
+                    .flat_map(|else_| else_.body.loop_control_stmts()),
+            )
+            .collect()
+    }
+
+    fn blocks(&self) -> Vec<&Block> {
+        iter::once(&*self.block)
+            .chain(self.else_.iter().flat_map(|else_| else_.body.blocks()))
+            .collect()
+    }
+
+    fn conditions(&self) -> Vec<&MaybeBinaryExpr> {
+        iter::once(&*self.condition.expr)
+            .chain(self.else_.iter().flat_map(|else_| else_.body.conditions()))
+            .collect()
+    }
+}
+
+sequence!(
+    struct ElseBranch {
+        else_: ElseKeyword,
+        #[force_error(true)]
+        body: ElseBody,
+    }
+);
+
+impl NodeConfig for ElseBranch {
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let body = self.body.transpile(ctx, generic_args);
+        let stmts = mem::take(&mut ctx.generated_stmts)
+            .into_iter()
+            .chain([body])
+            .join("\n");
+        format!(" else {{\n{stmts}\n}}")
+    }
+}
+
+choice!(
+    enum ElseBody {
+        If(IfStmt),
+        Block(Block),
+    }
+);
+
+impl ElseBody {
+    fn is_returning(&self) -> bool {
+        match self {
+            Self::If(stmt) => stmt.is_returning(),
+            Self::Block(block) => block.is_returning(),
+        }
+    }
+
+    fn return_stmts(&self) -> Vec<&ReturnStmt> {
+        match self {
+            Self::If(stmt) => stmt.return_stmts(),
+            Self::Block(block) => block.return_stmts(),
+        }
+    }
+
+    fn loop_control_stmts(&self) -> Vec<&Stmt> {
+        match self {
+            Self::If(stmt) => stmt.loop_control_stmts(),
+            Self::Block(block) => block.loop_control_stmts(),
+        }
+    }
+
+    fn blocks(&self) -> Vec<&Block> {
+        match self {
+            Self::If(stmt) => stmt.blocks(),
+            Self::Block(block) => vec![block],
+        }
+    }
+
+    fn conditions(&self) -> Vec<&MaybeBinaryExpr> {
+        match self {
+            Self::If(stmt) => stmt.conditions(),
+            Self::Block(_) => vec![],
+        }
+    }
+}
+
+sequence!(
+    struct MatchStmt {
+        match_: MatchKeyword,
+        #[force_error(true)]
+        expr: BlockHeaderExpr,
+        arms_start: OpenCurlyBracketSymbol,
+        arms: Repeated<MatchArm, 1, { usize::MAX }>,
+        arms_end: CloseCurlyBracketSymbol,
+    }
+);
+
+impl NodeConfig for MatchStmt {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        let Some(type_) = self.expr.type_(ctx.index) else {
+            return;
+        };
+        let enum_ = enum_::enum_item(&type_);
+        if enum_.is_none() && !is_integer_type(&type_, self, ctx.index) {
+            let type_name = type_.name_or_no_return(ctx.index);
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.expr,
+                "invalid expression type",
+                Some(&format!(
+                    "expression type is `{type_name}` but expected type is an enum, `i32` or `u32`"
+                )),
+                &[],
+            ));
+            return;
+        }
+        let mut default_arm: Option<&MatchArm> = None;
+        let mut matched_variants: Vec<(&MatchArm, &str)> = vec![];
+        let mut matched_values: Vec<(&ChainExpr, ConstantData)> = vec![];
+        for arm in self.arms.iter() {
+            if let Some(default_arm) = default_arm {
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    &**arm,
+                    "unreachable `match` arm",
+                    Some("this arm is never executed"),
+                    &[(default_arm, "all remaining values are matched here")],
+                ));
+                continue;
+            }
+            match &*arm.pattern {
+                MatchPattern::Default(_) => default_arm = Some(arm),
+                MatchPattern::Variant(pattern) => {
+                    if !pattern.check_type(&*self.expr, &type_, ctx) {
+                        continue;
+                    }
+                    let Some(enum_) = enum_ else {
+                        continue;
+                    };
+                    let name = pattern.variant.slice.as_str();
+                    if let Some((other_arm, _)) = matched_variants
+                        .iter()
+                        .find(|(_, other_name)| *other_name == name)
+                    {
+                        ctx.errors.push(ValidationError::error(
+                            ctx,
+                            &**arm,
+                            "`match` arm defined multiple times",
+                            Some("duplicated arm"),
+                            &[(*other_arm, "same arm defined here")],
+                        ));
+                    } else if enum_.variant(name).is_some() {
+                        matched_variants.push((arm, name));
+                    }
+                }
+                MatchPattern::Values(values) => {
+                    for value in values.iter() {
+                        validations::check_invalid_expr_type(&*self.expr, value, true, ctx);
+                        let Some(data) = constant_match_value(value, ctx) else {
+                            continue;
+                        };
+                        if let Some((other_value, _)) = matched_values
+                            .iter()
+                            .find(|(_, other_data)| *other_data == data)
+                        {
+                            ctx.errors.push(ValidationError::error(
+                                ctx,
+                                value,
+                                "`match` value defined multiple times",
+                                Some("duplicated value"),
+                                &[(*other_value, "same value defined here")],
+                            ));
+                        } else {
+                            matched_values.push((value, data));
+                        }
+                    }
+                }
+            }
+        }
+        if default_arm.is_none() {
+            self.check_exhaustiveness(enum_, &matched_variants, ctx);
+        }
+    }
+
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        if self
+            .expr
+            .type_(index)
+            .is_some_and(|type_| enum_::enum_item(&type_).is_some())
+        {
+            return Some(self);
+        }
+        self.expr.invalid_constant(index).or_else(|| {
+            self.arms
+                .iter()
+                .find_map(|arm| arm.block.invalid_constant(index))
+        })
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        let value = self.expr.evaluate_constant(ctx)?;
+        let arm = self
+            .arms
+            .iter()
+            .find(|arm| arm.pattern.is_matching(&value, ctx))?;
+        arm.block.evaluate_constant(ctx)
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let expr = self.expr.transpile(ctx, generic_args);
+        let var_name = format!("_{}", ctx.next_node_id());
+        ctx.generated_stmts
+            .push(format!("var {var_name} = {expr};"));
+        let type_ = self
+            .expr
+            .type_(ctx.index)
+            .expect("internal error: match expression type not found")
+            .specialized(generic_args, ctx.index);
+        let enum_ = enum_::enum_item(&type_);
+        let is_return_propagated =
+            ctx.inline_state.return_var_id.is_some() && !self.return_stmts().is_empty();
+        if is_return_propagated && ctx.inline_state.returned_flag_id.is_none() {
+            ctx.inline_state.returned_flag_id = Some(ctx.next_node_id());
+        }
+        let is_break_propagated = self
+            .loop_control_stmts()
+            .iter()
+            .any(|stmt| stmt.as_break().is_some());
+        let break_flag_id = is_break_propagated.then(|| ctx.next_node_id());
+        let header_stmts = mem::take(&mut ctx.generated_stmts);
+        let outer_break_flag_id = mem::replace(&mut ctx.break_flag_id, break_flag_id);
+        let has_default_arm = self
+            .arms
+            .iter()
+            .any(|arm| arm.pattern.as_default().is_some());
+        let arm_count = self.arms.iter().len();
+        let arms = self
+            .arms
+            .iter()
+            .enumerate()
+            .map(|(position, arm)| {
+                let is_default = !has_default_arm && position == arm_count - 1;
+                arm.transpile_case(ctx, &var_name, enum_, &type_, is_default, generic_args)
+            })
+            .join("\n");
+        ctx.break_flag_id = outer_break_flag_id;
+        ctx.generated_stmts = header_stmts;
+        let selector = if enum_.is_some() {
+            format!("{var_name}.tag")
+        } else {
+            var_name
+        };
+        let mut stmts = vec![format!("switch {selector} {{\n{arms}\n}}")];
+        if let Some(flag_id) = break_flag_id {
+            ctx.generated_stmts.push(format!("var _{flag_id} = false;"));
+            let break_ = transpile_break(ctx);
+            stmts.push(format!("if _{flag_id} {{\n{break_}\n}}"));
+        }
+        match ctx.inline_state.returned_flag_id {
+            Some(flag_id) if is_return_propagated => {
+                stmts.push(format!("if _{flag_id} {{\nbreak;\n}}"));
+            }
+            _ => {}
+        }
+        stmts.join("\n")
+    }
+}
+
+impl MatchStmt {
+    fn check_exhaustiveness(
+        &self,
+        enum_: Option<&EnumItem>,
+        matched_variants: &[(&MatchArm, &str)],
+        ctx: &mut ValidationContext<'_>,
+    ) {
+        let Some(enum_) = enum_ else {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.expr,
+                "non-exhaustive `match` statement",
+                Some("missing `_` arm"),
+                &[],
+            ));
+            return;
+        };
+        let missing_variants: Vec<_> = enum_
+            .variants()
+            .filter(|variant| {
+                !matched_variants
+                    .iter()
+                    .any(|(_, name)| *name == variant.ident.slice)
+            })
+            .map(|variant| format!("`{}`", variant.ident.slice))
+            .collect();
+        if !missing_variants.is_empty() {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.expr,
+                "non-exhaustive `match` statement",
+                Some(&format!(
+                    "missing variants: {}",
+                    missing_variants.join(", ")
+                )),
+                &[],
+            ));
+        }
+    }
+
+    fn is_returning(&self) -> bool {
+        self.arms.iter().all(|arm| arm.block.is_returning())
+    }
+
+    fn return_stmts(&self) -> Vec<&ReturnStmt> {
+        self.arms
+            .iter()
+            .flat_map(|arm| arm.block.return_stmts())
+            .collect()
+    }
+
+    fn loop_control_stmts(&self) -> Vec<&Stmt> {
+        self.arms
+            .iter()
+            .flat_map(|arm| arm.block.loop_control_stmts())
+            .collect()
+    }
+}
+
+sequence!(
+    struct MatchArm {
+        pattern: MatchPattern,
+        #[force_error(true)]
+        arrow: FatArrowSymbol,
+        block: Block,
+        comma: Repeated<CommaSymbol, 0, 1>,
+    }
+);
+
+impl NodeConfig for MatchArm {}
+
+impl MatchArm {
+    fn transpile_case(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        var_name: &str,
+        enum_: Option<&EnumItem>,
+        type_: &NodeSource<'_>,
+        is_default: bool,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let mut stmts = vec![];
+        let selector = match &*self.pattern {
+            MatchPattern::Default(_) => "default".into(),
+            MatchPattern::Values(values) => {
+                let values = values
+                    .iter()
+                    .map(|value| {
+                        let value = value
+                            .evaluate_constant(&mut ConstantContext::new(ctx.index))
+                            .expect("internal error: invalid `match` value");
+                        constant::transpile_constant_value(&value)
+                    })
+                    .join(", ");
+                format!("case {values}")
+            }
+            MatchPattern::Variant(pattern) => {
+                let enum_ = enum_.expect("internal error: invalid enum type");
+                let variant = enum_
+                    .variant(&pattern.variant.slice)
+                    .expect("internal error: variant not found");
+                if pattern.binding.iter().len() > 0 {
+                    let binding_name = if ctx.inline_state.is_inlined {
+                        let id = ctx.next_node_id();
+                        let binding_name = format!("_{id}");
+                        ctx.add_inline_mapping(pattern.id, &binding_name);
+                        binding_name
+                    } else {
+                        format!("_{}", pattern.id)
+                    };
+                    let payload_fn_name =
+                        enum_.transpiled_payload_fn_name(variant, ctx.index, &type_.generic_args);
+                    stmts.push(format!(
+                        "var {binding_name} = {payload_fn_name}({var_name});"
+                    ));
+                }
+                let tag = enum_.tag(variant);
+                if is_default {
+                    format!("case {tag}u, default")
+                } else {
+                    format!("case {tag}u")
+                }
+            }
+        };
+        stmts.push(self.block.transpile(ctx, generic_args));
+        let stmts = stmts.join("\n");
+        format!("{selector}: {{\n{stmts}\n}}")
+    }
+}
+
+choice!(
+    enum MatchPattern {
+        Default(UnderscoreSymbol),
+        Variant(EnumVariantPattern),
+        Values(ValuePatterns),
+    }
+);
+
+impl MatchPattern {
+    fn is_matching(&self, value: &ConstantValue, ctx: &mut ConstantContext<'_>) -> bool {
+        match self {
+            Self::Default(_) => true,
+            Self::Values(values) => values.iter().any(|pattern_value| {
+                pattern_value
+                    .evaluate_constant(ctx)
+                    .is_some_and(|pattern_value| pattern_value.data == value.data)
+            }),
+            Self::Variant(_) => unreachable!("enums cannot be evaluated as constants"),
+        }
+    }
+}
+
+fn is_integer_type(type_: &NodeSource<'_>, node: &impl Node, index: &NodeIndex) -> bool {
+    [
+        I32Literal::i32_type(node, index),
+        U32Literal::u32_type(node, index),
+    ]
+    .into_iter()
+    .any(|integer_type| type_.type_item().is_some_and(|t| t.id == integer_type.id))
+}
+
+fn constant_match_value(
+    value: &ChainExpr,
+    ctx: &mut ValidationContext<'_>,
+) -> Option<ConstantData> {
+    value.type_(ctx.index)?;
+    let data = if value.invalid_constant(ctx.index).is_none() {
+        value
+            .evaluate_constant(&mut ConstantContext::new(ctx.index))
+            .map(|value| value.data)
+    } else {
+        None
+    };
+    if data.is_none() {
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            value,
+            "invalid `match` value",
+            Some("value must be known at compile time"),
+            &[],
+        ));
+    }
+    data
+}
+
+sequence!(
+    #[allow(unused_mut)]
+    struct ValuePatterns {
+        first_value: ChainExpr,
+        other_values: Repeated<OtherValuePattern, 0, { usize::MAX }>,
+    }
+);
+
+impl NodeConfig for ValuePatterns {}
+
+impl ValuePatterns {
+    fn iter(&self) -> impl Iterator<Item = &ChainExpr> {
+        iter::once(&*self.first_value).chain(self.other_values.iter().map(|other| &*other.value))
+    }
+}
+
+sequence!(
+    struct OtherValuePattern {
+        pipe: PipeSymbol,
+        #[force_error(true)]
+        value: ChainExpr,
+    }
+);
+
+impl NodeConfig for OtherValuePattern {}
+
+sequence!(
+    struct EnumVariantPattern {
+        type_: NamedType,
+        separator: DoubleColonSymbol,
+        #[force_error(true)]
+        variant: Ident,
+        binding: Repeated<EnumVariantBinding, 0, 1>,
+    }
+);
+
+impl NodeConfig for EnumVariantPattern {
+    fn key(&self) -> Option<String> {
+        self.binding
+            .iter()
+            .next()
+            .map(|binding| sources::variable_key(&binding.ident))
+    }
+
+    fn source_key(&self, index: &NodeIndex) -> Option<String> {
+        let type_ = self.type_.type_(index)?;
+        Some(sources::variant_key(
+            &self.variant,
+            &type_.name_or_no_return(index),
+        ))
+    }
+
+    fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        let type_ = self.type_.type_(index)?;
+        enum_::enum_item(&type_)?.variant(&self.variant.slice)?;
+        Some(type_)
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        let type_ = self.source(index)?;
+        enum_::enum_item(&type_)?
+            .variant(&self.variant.slice)?
+            .payload_type(index, &type_.generic_args)
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        validations::check_missing_source(self, ctx);
+        let Some(type_) = self.source(ctx.index) else {
+            return;
+        };
+        let variant = enum_::enum_item(&type_)
+            .and_then(|enum_| enum_.variant(&self.variant.slice))
+            .expect("internal error: variant not found");
+        if let Some(binding) = self.binding.iter().next() {
+            if !variant.has_payload() {
+                let name = &variant.ident.slice;
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    &**binding,
+                    "unexpected variant payload",
+                    Some(&format!("`{name}` variant has no payload")),
+                    &[(variant, "variant defined here")],
+                ));
+            }
+        }
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+}
+
+impl EnumVariantPattern {
+    fn check_type(
+        &self,
+        expr: &dyn Node,
+        expected_type: &NodeSource<'_>,
+        ctx: &mut ValidationContext<'_>,
+    ) -> bool {
+        let Some(type_) = self.type_.type_(ctx.index) else {
+            return false;
+        };
+        if type_.are_same_types(expected_type, ctx.index) == Some(false) {
+            let type_name = type_.name_or_no_return(ctx.index);
+            let expected_type_name = expected_type.name_or_no_return(ctx.index);
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                self,
+                "invalid pattern type",
+                Some(&format!("pattern type is `{type_name}`")),
+                &[(expr, &format!("expected type is `{expected_type_name}`"))],
+            ));
+            false
+        } else {
+            true
+        }
+    }
+}
+
+sequence!(
+    struct EnumVariantBinding {
+        start: OpenParenthesisSymbol,
+        #[force_error(true)]
+        ident: Ident,
+        end: CloseParenthesisSymbol,
+    }
+);
+
+impl NodeConfig for EnumVariantBinding {}
+
+sequence!(
+    struct ForStmt {
+        for_: ForKeyword,
+        #[force_error(true)]
+        range: ForRange,
+        block: Block,
+    }
+);
+
+impl NodeConfig for ForStmt {
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.range
+            .invalid_constant(index)
+            .or_else(|| self.block.invalid_constant(index))
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        let start = self.range.start.evaluate_constant(ctx)?;
+        let end = self.range.end.evaluate_constant(ctx)?;
+        let values: Box<dyn Iterator<Item = ConstantData>> = match (start.data, end.data) {
+            (ConstantData::I32(start), ConstantData::I32(end)) => {
+                Box::new((start..end).map(ConstantData::I32))
+            }
+            (ConstantData::U32(start), ConstantData::U32(end)) => {
+                Box::new((start..end).map(ConstantData::U32))
+            }
+            (_, _) => unreachable!("invalid const range bounds"),
+        };
+        for data in values {
+            let value = ConstantValue {
+                transpiled_type_name: start.transpiled_type_name.clone(),
+                data,
+            };
+            ctx.create_var(self.range.id, value);
+            if let ControlFlow::Break(returned_value) = evaluate_loop_iteration(ctx, &self.block) {
+                return returned_value;
+            }
+        }
+        None
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let start = self.range.start.transpile(ctx, generic_args);
+        let start_var_name = format!("_{}", ctx.next_node_id());
+        ctx.generated_stmts
+            .push(format!("var {start_var_name} = {start};"));
+        let end = self.range.end.transpile(ctx, generic_args);
+        let end_var_name = format!("_{}", ctx.next_node_id());
+        ctx.generated_stmts
+            .push(format!("var {end_var_name} = {end};"));
+        let var_name = if ctx.inline_state.is_inlined {
+            let id = ctx.next_node_id();
+            let var_name = format!("_{id}");
+            ctx.add_inline_mapping(self.range.id, &var_name);
+            var_name
+        } else {
+            format!("_{}", self.range.id)
+        };
+        let header = format!(
+            "for (var {var_name} = {start_var_name}; {var_name} < {end_var_name}; {var_name}++)"
+        );
+        transpile_loop(ctx, &header, vec![], &self.block, generic_args)
+    }
+}
+
+impl ForStmt {
+    // All invocations run the same iterations when the bounds are constants
+    // and no statement exits the loop early.
+    pub(crate) fn is_uniform(&self, index: &NodeIndex) -> bool {
+        let mut ctx = ConstantContext::new(index);
+        self.range.invalid_constant(index).is_none()
+            && self.range.start.evaluate_constant(&mut ctx).is_some()
+            && self.range.end.evaluate_constant(&mut ctx).is_some()
+            && self.block.loop_control_stmts().is_empty()
+    }
+}
+
+sequence!(
+    struct ForRange {
+        ident: Ident,
+        #[force_error(true)]
+        in_: InKeyword,
+        start: MaybeBinaryExpr,
+        dots: DoubleDotSymbol,
+        end: BlockHeaderExpr,
+    }
+);
+
+impl NodeConfig for ForRange {
+    fn key(&self) -> Option<String> {
+        Some(sources::variable_key(&self.ident))
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.start.type_(index)
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        if !validations::check_integer_expr_type(&*self.start, ctx) {
+            return;
+        }
+        validations::check_invalid_expr_type(&*self.start, &*self.end, true, ctx);
+    }
+
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.start
+            .invalid_constant(index)
+            .or_else(|| self.end.invalid_constant(index))
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+}
+
+sequence!(
+    struct WhileStmt {
+        while_: WhileKeyword,
+        #[force_error(true)]
+        condition: BlockHeaderExpr,
+        block: Block,
+    }
+);
+
+impl NodeConfig for WhileStmt {
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        let bool_type = NodeSource {
+            node: NodeRef::Type(simple::bool_type(self, ctx.index)),
+            generic_args: vec![],
+        };
+        validations::check_invalid_const_expr_type(&bool_type, &*self.condition, ctx);
+    }
+
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.condition
+            .invalid_constant(index)
+            .or_else(|| self.block.invalid_constant(index))
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        loop {
+            let condition = self.condition.evaluate_constant(ctx)?;
+            if !matches!(condition.data, ConstantData::Bool(true)) {
+                return None;
+            }
+            if let ControlFlow::Break(returned_value) = evaluate_loop_iteration(ctx, &self.block) {
+                return returned_value;
+            }
+        }
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        let condition = self.condition.transpile(ctx, generic_args);
+        let condition_stmts = mem::take(&mut ctx.generated_stmts)
+            .into_iter()
+            .chain([format!("if !bool({condition}) {{\nbreak;\n}}")])
+            .collect();
+        transpile_loop(ctx, "loop", condition_stmts, &self.block, generic_args)
+    }
+}
+
+sequence!(
+    struct LoopStmt {
+        loop_: LoopKeyword,
+        #[force_error(true)]
+        block: Block,
+    }
+);
+
+impl NodeConfig for LoopStmt {
+    fn invalid_constant(&self, index: &NodeIndex) -> Option<&dyn Node> {
+        self.block.invalid_constant(index)
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        loop {
+            if let ControlFlow::Break(returned_value) = evaluate_loop_iteration(ctx, &self.block) {
+                return returned_value;
+            }
+        }
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        transpile_loop(ctx, "loop", vec![], &self.block, generic_args)
+    }
+}
+
+impl LoopStmt {
+    // A loop without `break` can only be exited with a `return` statement.
+    fn is_returning(&self) -> bool {
+        self.block
+            .loop_control_stmts()
+            .iter()
+            .all(|stmt| stmt.as_break().is_none())
+    }
+}
+
+sequence!(
+    struct BreakStmt {
+        break_: BreakKeyword,
+        #[force_error(true)]
+        semicolon: SemicolonSymbol,
+    }
+);
+
+impl NodeConfig for BreakStmt {
+    fn invalid_constant(&self, _index: &NodeIndex) -> Option<&dyn Node> {
+        None
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        ctx.interruption = Some(ConstantInterruption::Break);
+        None
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        _generic_args: &GenericArgs<'_>,
+    ) -> String {
+        transpile_break(ctx)
+    }
+}
+
+sequence!(
+    struct ContinueStmt {
+        continue_: ContinueKeyword,
+        #[force_error(true)]
+        semicolon: SemicolonSymbol,
+    }
+);
+
+impl NodeConfig for ContinueStmt {
+    fn invalid_constant(&self, _index: &NodeIndex) -> Option<&dyn Node> {
+        None
+    }
+
+    fn evaluate_constant(&self, ctx: &mut ConstantContext<'_>) -> Option<ConstantValue> {
+        ctx.interruption = Some(ConstantInterruption::Continue);
+        None
+    }
+
+    fn transpile(
+        &self,
+        _ctx: &mut TranspilationContext<'_>,
+        _generic_args: &GenericArgs<'_>,
+    ) -> String {
+        "continue;".into()
+    }
+}
+
+// A `break` nested in a `match` statement sets a flag, as it would only exit the WGSL `switch`.
+fn transpile_break(ctx: &TranspilationContext<'_>) -> String {
+    if let Some(flag_id) = ctx.break_flag_id {
+        format!("_{flag_id} = true;\nbreak;")
+    } else {
+        "break;".into()
+    }
+}
+
+// Returns `ControlFlow::Break` with the returned value when the loop should be exited.
+fn evaluate_loop_iteration(
+    ctx: &mut ConstantContext<'_>,
+    block: &Block,
+) -> ControlFlow<Option<ConstantValue>> {
+    if !ctx.start_loop_iteration() {
+        return ControlFlow::Break(None);
+    }
+    if let Some(returned_value) = block.evaluate_constant(ctx) {
+        return ControlFlow::Break(Some(returned_value));
+    }
+    match ctx.interruption {
+        Some(ConstantInterruption::Break) => {
+            ctx.interruption = None;
+            ControlFlow::Break(None)
+        }
+        Some(ConstantInterruption::Continue) => {
+            ctx.interruption = None;
+            ControlFlow::Continue(())
+        }
+        Some(
+            ConstantInterruption::LoopIterationLimit
+            | ConstantInterruption::IndexOutOfBounds
+            | ConstantInterruption::NonFiniteValue
+            | ConstantInterruption::DivisionByZero
+            | ConstantInterruption::MissingNativeFnImplementation,
+        ) => ControlFlow::Break(None),
+        None => ControlFlow::Continue(()),
+    }
+}
+
+// A loop containing a `return` statement of an inlined function sets a flag before exiting
+// the loop, so that the `return` is propagated to the enclosing blocks.
+fn transpile_loop(
+    ctx: &mut TranspilationContext<'_>,
+    header: &str,
+    first_stmts: Vec<String>,
+    block: &Block,
+    generic_args: &GenericArgs<'_>,
+) -> String {
+    let is_return_propagated =
+        ctx.inline_state.return_var_id.is_some() && !block.return_stmts().is_empty();
+    if is_return_propagated && ctx.inline_state.returned_flag_id.is_none() {
+        ctx.inline_state.returned_flag_id = Some(ctx.next_node_id());
+    }
+    let header_stmts = mem::take(&mut ctx.generated_stmts);
+    let outer_break_flag_id = ctx.break_flag_id.take();
+    let block = first_stmts
+        .into_iter()
+        .chain([block.transpile(ctx, generic_args)])
+        .join("\n");
+    ctx.break_flag_id = outer_break_flag_id;
+    ctx.generated_stmts = header_stmts;
+    match ctx.inline_state.returned_flag_id {
+        Some(flag_id) if is_return_propagated => {
+            format!("{header} {{\n{block}\n}}\nif _{flag_id} {{\nbreak;\n}}")
+        }
+        _ => format!("{header} {{\n{block}\n}}"),
+    }
+}
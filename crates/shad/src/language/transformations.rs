@@ -5,6 +5,8 @@ use crate::language::expressions::binary::{
 use crate::language::expressions::chain::{
     ChainExpr, ChainSuffix, ParsedChainExpr, TransformedChainExpr,
 };
+use crate::language::expressions::simple::{ParenthesizedExpr, ParsedParenthesizedExpr};
+use crate::language::expressions::tuple::TupleExpr;
 use itertools::Itertools;
 use std::iter;
 use std::rc::Rc;
@@ -13,6 +15,10 @@ const OPERATOR_PRIORITY: &[&[&str]] = &[
     &["||"],
     &["&&"],
     &["<", ">", "<=", ">=", "==", "!="],
+    &["|"],
+    &["^"],
+    &["&"],
+    &["<<", ">>"],
     &["+", "-"],
     &["*", "/", "%"],
 ];
@@ -40,6 +46,19 @@ pub(crate) fn transform_chain_expr(expr: ParsedChainExpr) -> ChainExpr {
     ChainExpr::Transformed(Rc::new(transform_chain_expr_inner(prefix, suffix)))
 }
 
+pub(crate) fn transform_parenthesized_expr(expr: ParsedParenthesizedExpr) -> ParenthesizedExpr {
+    if expr.other_items.iter().len() == 0 {
+        return ParenthesizedExpr::Parsed(Rc::new(expr));
+    }
+    ParenthesizedExpr::Transformed(Rc::new(TupleExpr {
+        start: expr.open,
+        first_item: expr.expr,
+        other_items: expr.other_items,
+        end: expr.close,
+        props: expr.props,
+    }))
+}
+
 fn transform_binary_expr_inner(
     operators: &[&Rc<BinaryOperator>],
     operands: &[&Rc<ChainExpr>],
@@ -1,24 +1,53 @@
+use crate::compilation::constant::{ConstantContext, ConstantData};
 use crate::compilation::index::NodeIndex;
-use crate::compilation::node::{sequence, GenericArgs, NodeConfig, NodeRef, NodeSource, Repeated};
+use crate::compilation::node::{
+    choice, sequence, GenericArgs, GenericValue, Node, NodeConfig, NodeRef, NodeSource, Repeated,
+    UNKNOWN_TYPE,
+};
 use crate::compilation::transpilation::TranspilationContext;
 use crate::compilation::validation::ValidationContext;
+use crate::compilation::PRELUDE_PATH;
+use crate::language::expressions::binary::MaybeBinaryExpr;
+use crate::language::expressions::chain::ChainExpr;
+use crate::language::expressions::simple::VarIdentExpr;
+use crate::language::expressions::tuple::TupleExpr;
+use crate::language::items::enum_;
+use crate::language::items::is_item_recursive;
 use crate::language::items::type_;
-use crate::language::items::type_::TypeItem;
-use crate::language::keywords::{CloseAngleBracketSymbol, CommaSymbol, OpenAngleBracketSymbol};
-use crate::language::patterns::Ident;
+use crate::language::items::type_::{
+    GenericParam, NativeStructItem, StructField, StructItem, TypeItem,
+};
+use crate::language::keywords::{
+    CloseAngleBracketSymbol, CloseParenthesisSymbol, CloseSquareBracketSymbol, CommaSymbol,
+    OpenAngleBracketSymbol, OpenParenthesisSymbol, OpenSquareBracketSymbol, SemicolonSymbol,
+};
+use crate::language::patterns::{Ident, U32Literal};
 use crate::language::{sources, validations};
 use crate::ValidationError;
+use itertools::Itertools;
+use std::any::Any;
 use std::iter;
+use std::path::Path;
+
+const SWIZZLE_COMPONENTS: [&str; 2] = ["xyzw", "rgba"];
+
+choice!(
+    enum Type {
+        Array(ArrayType),
+        Tuple(TupleType),
+        Named(NamedType),
+    }
+);
 
 sequence!(
     #[allow(unused_mut)]
-    struct Type {
+    struct NamedType {
         ident: Ident,
         generics: Repeated<TypeGenericArgs, 0, 1>,
     }
 );
 
-impl NodeConfig for Type {
+impl NodeConfig for NamedType {
     fn source_key(&self, _index: &NodeIndex) -> Option<String> {
         Some(sources::type_key(&self.ident))
     }
@@ -26,12 +55,7 @@ impl NodeConfig for Type {
     fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
         Some(NodeSource {
             node: NodeRef::Type(self.item(index)?),
-            generic_args: self
-                .generics
-                .iter()
-                .flat_map(|generics| generics.args())
-                .map(|arg| arg.source(index))
-                .collect(),
+            generic_args: generic_args(&self.generics, index),
         })
     }
 
@@ -42,41 +66,24 @@ impl NodeConfig for Type {
     fn validate(&self, ctx: &mut ValidationContext<'_>) {
         validations::check_missing_source(self, ctx);
         if let Some(item) = self.item(ctx.index) {
-            let generic_params = item.generic_params();
-            let expected_param_count = generic_params.len();
-            let actual_param_count = self
-                .generics
-                .iter()
-                .next()
-                .map_or(0, |args| 1 + args.other_args.iter().len());
-            if expected_param_count != actual_param_count {
-                ctx.errors.push(ValidationError::error(
-                    ctx,
-                    self,
-                    "invalid number of generic parameters",
-                    Some(&format!("{actual_param_count} parameter(s) specified here")),
-                    &[(
-                        item,
-                        &format!("{expected_param_count} parameter(s) expected"),
-                    )],
-                ));
-            }
+            check_generic_args(self, item, &self.generics, ctx);
         }
     }
 
     fn transpile(
         &self,
         ctx: &mut TranspilationContext<'_>,
-        _generic_args: &GenericArgs<'_>,
+        generic_args: &GenericArgs<'_>,
     ) -> String {
         self.type_(ctx.index)
             .expect("internal error: type not found")
+            .specialized(generic_args, ctx.index)
             .transpiled_type_name(ctx.index)
     }
 }
 
-impl Type {
-    pub(crate) fn item<'a>(&self, index: &'a NodeIndex) -> Option<&'a dyn TypeItem> {
+impl NamedType {
+    fn item<'a>(&self, index: &'a NodeIndex) -> Option<&'a dyn TypeItem> {
         let source = index.search(self, &self.source_key(index)?, sources::type_criteria())?;
         Some(type_::to_item(source))
     }
@@ -86,7 +93,7 @@ sequence!(
     struct TypeGenericArgs {
         start: OpenAngleBracketSymbol,
         #[force_error(true)]
-        first_arg: Type,
+        first_arg: GenericArg,
         other_args: Repeated<TypeOtherGenericArg, 0, { usize::MAX }>,
         final_comma: Repeated<CommaSymbol, 0, 1>,
         end: CloseAngleBracketSymbol,
@@ -96,17 +103,866 @@ sequence!(
 impl NodeConfig for TypeGenericArgs {}
 
 impl TypeGenericArgs {
-    pub(crate) fn args(&self) -> impl Iterator<Item = &Type> {
+    pub(crate) fn args(&self) -> impl Iterator<Item = &GenericArg> {
         iter::once(&*self.first_arg).chain(self.other_args.iter().map(|other| &*other.arg))
     }
+
+    pub(crate) fn values<'a>(&'a self, index: &'a NodeIndex) -> GenericArgs<'a> {
+        self.args().map(|arg| arg.value(index)).collect()
+    }
 }
 
 sequence!(
     #[allow(unused_mut)]
     struct TypeOtherGenericArg {
         comma: CommaSymbol,
-        arg: Type,
+        arg: GenericArg,
     }
 );
 
 impl NodeConfig for TypeOtherGenericArg {}
+
+choice!(
+    enum GenericArg {
+        Array(ArrayType),
+        Tuple(TupleType),
+        Named(NamedGenericArg),
+        Value(ChainExpr),
+    }
+);
+
+impl GenericArg {
+    fn value<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        match self {
+            Self::Array(type_) => type_.type_(index),
+            Self::Tuple(type_) => type_.type_(index),
+            Self::Named(arg) => arg.source(index),
+            Self::Value(expr) => constant_generic_arg(&**expr, expr.as_var_ident(), index),
+        }
+    }
+}
+
+// A named generic argument is either a type, a constant or a generic value parameter.
+sequence!(
+    #[allow(unused_mut)]
+    struct NamedGenericArg {
+        ident: Ident,
+        generics: Repeated<TypeGenericArgs, 0, 1>,
+    }
+);
+
+impl NodeConfig for NamedGenericArg {
+    fn source_key(&self, _index: &NodeIndex) -> Option<String> {
+        Some(sources::type_key(&self.ident))
+    }
+
+    fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        if let Some(type_) = self.type_item(index) {
+            return Some(NodeSource {
+                node: NodeRef::Type(type_),
+                generic_args: generic_args(&self.generics, index),
+            });
+        }
+        if self.generics.iter().len() > 0 {
+            return None;
+        }
+        let key = sources::variable_key(&self.ident);
+        let source = index.search(self, &key, sources::generic_value_criteria())?;
+        if let Some(param) = (source as &dyn Any).downcast_ref::<GenericParam>() {
+            Some(NodeSource {
+                node: NodeRef::Type(param),
+                generic_args: vec![],
+            })
+        } else {
+            let mut ctx = ConstantContext::new(index);
+            let value = source.evaluate_constant(&mut ctx)?;
+            Some(NodeSource {
+                node: NodeRef::Value(GenericValue::from_constant(&value.data)?),
+                generic_args: vec![],
+            })
+        }
+    }
+
+    // For constants and generic value parameters, this is the type of the value.
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        if self.type_item(index).is_some() {
+            self.source(index)
+        } else {
+            self.value_type(index)
+        }
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        validations::check_missing_source(self, ctx);
+        if let Some(item) = self.type_item(ctx.index) {
+            check_generic_args(self, item, &self.generics, ctx);
+        }
+    }
+}
+
+impl NamedGenericArg {
+    fn type_item<'a>(&self, index: &'a NodeIndex) -> Option<&'a dyn TypeItem> {
+        let source = index.search(self, &self.source_key(index)?, sources::type_criteria())?;
+        Some(type_::to_item(source))
+    }
+
+    fn value_type<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        let key = sources::variable_key(&self.ident);
+        index
+            .search(self, &key, sources::generic_value_criteria())?
+            .type_(index)
+    }
+}
+
+sequence!(
+    struct ArrayType {
+        start: OpenSquareBracketSymbol,
+        #[force_error(true)]
+        item_type: Type,
+        semicolon: SemicolonSymbol,
+        length: MaybeBinaryExpr,
+        end: CloseSquareBracketSymbol,
+    }
+);
+
+impl NodeConfig for ArrayType {
+    // The length is stored as a generic argument, so that it can depend on generic parameters.
+    fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        Some(NodeSource {
+            node: NodeRef::Type(self),
+            generic_args: vec![
+                self.item_type.type_(index),
+                constant_generic_arg(&*self.length, self.length.as_var_ident(), index),
+            ],
+        })
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.source(index)
+    }
+
+    fn validate(&self, ctx: &mut ValidationContext<'_>) {
+        let u32_type = NodeSource {
+            node: NodeRef::Type(U32Literal::u32_type(self, ctx.index)),
+            generic_args: vec![],
+        };
+        validations::check_invalid_const_expr_type(&u32_type, &*self.length, ctx);
+        validations::check_invalid_const_scope(&*self.length, self, ctx);
+        let length = validations::check_generic_value_evaluation(&*self.length, ctx);
+        if length.is_some_and(|length| length.data == ConstantData::U32(0)) {
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                &*self.length,
+                "invalid array length",
+                Some("array length must be greater than zero"),
+                &[],
+            ));
+        }
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        self.type_(ctx.index)
+            .expect("internal error: type not found")
+            .specialized(generic_args, ctx.index)
+            .transpiled_type_name(ctx.index)
+    }
+}
+
+impl TypeItem for ArrayType {
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    fn name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        let item_type_name = generic_args[0].as_ref().map_or_else(
+            || UNKNOWN_TYPE.into(),
+            |type_| type_.name_or_no_return(index),
+        );
+        let length = generic_args[1].as_ref().map_or_else(
+            || self.length.slice.clone(),
+            |length| length.name_or_no_return(index),
+        );
+        format!("[{item_type_name}; {length}]")
+    }
+
+    // Lengths are compared as generic arguments.
+    fn is_same_item(&self, other: &dyn TypeItem, _index: &NodeIndex) -> Option<bool> {
+        Some((other as &dyn Any).is::<Self>())
+    }
+
+    fn fields(&self) -> Vec<&StructField> {
+        vec![]
+    }
+
+    fn field(&self, _field_name: &str) -> Option<&StructField> {
+        None
+    }
+
+    fn size(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        let length = generic_length(generic_args).expect("internal error: invalid array length");
+        item_stride(generic_args, index) * length
+    }
+
+    fn alignment(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        type_::alignment(item_type(generic_args), index)
+    }
+
+    fn transpiled_name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        let item_type = generic_args[0]
+            .as_ref()
+            .expect("internal error: array item type not found")
+            .transpiled_type_name(index);
+        let length = generic_length(generic_args).expect("internal error: invalid array length");
+        format!("array<{item_type}, {length}>")
+    }
+
+    // coverage: off (never call in practice)
+    fn transpiled_field_name(&self, _field_name: &str) -> String {
+        unreachable!("arrays have no field")
+    }
+    // coverage: on
+}
+
+impl ArrayType {
+    pub(crate) fn length(&self, index: &NodeIndex) -> Option<u32> {
+        if self.length.invalid_constant(index).is_some() {
+            return None;
+        }
+        let mut ctx = ConstantContext::new(index);
+        if let ConstantData::U32(length) = self.length.evaluate_constant(&mut ctx)?.data {
+            Some(length)
+        } else {
+            None
+        }
+    }
+}
+
+sequence!(
+    struct RuntimeArrayType {
+        start: OpenSquareBracketSymbol,
+        #[force_error(true)]
+        item_type: Type,
+        end: CloseSquareBracketSymbol,
+    }
+);
+
+impl NodeConfig for RuntimeArrayType {
+    fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        Some(NodeSource {
+            node: NodeRef::Type(self),
+            generic_args: vec![self.item_type.type_(index)],
+        })
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.source(index)
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        self.type_(ctx.index)
+            .expect("internal error: type not found")
+            .specialized(generic_args, ctx.index)
+            .transpiled_type_name(ctx.index)
+    }
+}
+
+impl TypeItem for RuntimeArrayType {
+    fn is_native(&self) -> bool {
+        true
+    }
+
+    fn name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        let item_type_name = generic_args[0].as_ref().map_or_else(
+            || UNKNOWN_TYPE.into(),
+            |type_| type_.name_or_no_return(index),
+        );
+        format!("[{item_type_name}]")
+    }
+
+    fn is_same_item(&self, other: &dyn TypeItem, _index: &NodeIndex) -> Option<bool> {
+        Some((other as &dyn Any).is::<Self>())
+    }
+
+    fn fields(&self) -> Vec<&StructField> {
+        vec![]
+    }
+
+    fn field(&self, _field_name: &str) -> Option<&StructField> {
+        None
+    }
+
+    // As the item count is only known at runtime, the size of a single item is returned.
+    fn size(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        item_stride(generic_args, index)
+    }
+
+    fn alignment(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        type_::alignment(item_type(generic_args), index)
+    }
+
+    fn transpiled_name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        let item_type = generic_args[0]
+            .as_ref()
+            .expect("internal error: array item type not found")
+            .transpiled_type_name(index);
+        format!("array<{item_type}>")
+    }
+
+    // coverage: off (never call in practice)
+    fn transpiled_field_name(&self, _field_name: &str) -> String {
+        unreachable!("arrays have no field")
+    }
+    // coverage: on
+}
+
+sequence!(
+    #[allow(unused_mut)]
+    struct TupleType {
+        start: OpenParenthesisSymbol,
+        first_item_type: Type,
+        other_item_types: Repeated<TupleOtherItemType, 1, { usize::MAX }>,
+        end: CloseParenthesisSymbol,
+    }
+);
+
+impl NodeConfig for TupleType {
+    // Item types are stored as generic arguments, so that they can depend on generic parameters.
+    fn source<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        Some(NodeSource {
+            node: NodeRef::Type(self),
+            generic_args: self.item_types().map(|type_| type_.type_(index)).collect(),
+        })
+    }
+
+    fn type_<'a>(&'a self, index: &'a NodeIndex) -> Option<NodeSource<'a>> {
+        self.source(index)
+    }
+
+    fn is_transpilable_dependency(&self, _index: &NodeIndex) -> bool {
+        false
+    }
+
+    fn transpile(
+        &self,
+        ctx: &mut TranspilationContext<'_>,
+        generic_args: &GenericArgs<'_>,
+    ) -> String {
+        self.type_(ctx.index)
+            .expect("internal error: type not found")
+            .specialized(generic_args, ctx.index)
+            .transpiled_type_name(ctx.index)
+    }
+}
+
+impl TypeItem for TupleType {
+    fn is_native(&self) -> bool {
+        false
+    }
+
+    fn name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        tuple_name(index, generic_args)
+    }
+
+    fn is_same_item(&self, other: &dyn TypeItem, _index: &NodeIndex) -> Option<bool> {
+        Some(tuple_item_count(other) == Some(self.item_types().count()))
+    }
+
+    fn fields(&self) -> Vec<&StructField> {
+        vec![]
+    }
+
+    fn field(&self, _field_name: &str) -> Option<&StructField> {
+        None
+    }
+
+    fn size(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        tuple_size(index, generic_args)
+    }
+
+    fn alignment(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+        tuple_alignment(index, generic_args)
+    }
+
+    fn transpiled_name(&self, index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+        tuple_transpiled_name(index, generic_args)
+    }
+
+    fn transpiled_field_name(&self, field_name: &str) -> String {
+        format!("_{field_name}")
+    }
+}
+
+impl TupleType {
+    fn item_types(&self) -> impl Iterator<Item = &Type> {
+        iter::once(&*self.first_item_type)
+            .chain(self.other_item_types.iter().map(|other| &*other.type_))
+    }
+}
+
+sequence!(
+    #[allow(unused_mut)]
+    struct TupleOtherItemType {
+        comma: CommaSymbol,
+        type_: Type,
+    }
+);
+
+impl NodeConfig for TupleOtherItemType {}
+
+/// Returns the item types of a tuple type.
+pub(crate) fn tuple_item_types<'a>(type_: &NodeSource<'a>) -> Option<GenericArgs<'a>> {
+    type_
+        .type_item()
+        .is_some_and(|item| tuple_item_count(item).is_some())
+        .then(|| type_.generic_args.clone())
+}
+
+pub(crate) fn tuple_name(index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+    let item_type_names = generic_args
+        .iter()
+        .map(|type_| {
+            type_.as_ref().map_or_else(
+                || UNKNOWN_TYPE.into(),
+                |type_| type_.name_or_no_return(index),
+            )
+        })
+        .join(", ");
+    format!("({item_type_names})")
+}
+
+pub(crate) fn tuple_item_count(item: &dyn TypeItem) -> Option<usize> {
+    let item = item as &dyn Any;
+    if let Some(type_) = item.downcast_ref::<TupleType>() {
+        Some(type_.item_types().count())
+    } else {
+        item.downcast_ref::<TupleExpr>()
+            .map(|expr| expr.items().count())
+    }
+}
+
+pub(crate) fn tuple_size(index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+    let item_types = tuple_item_types_or_panic(generic_args);
+    let last_item_size = type_::size(&item_types[item_types.len() - 1], index);
+    type_::round_up(
+        tuple_alignment(index, generic_args),
+        type_::field_offset(&item_types, index) + last_item_size,
+    )
+}
+
+pub(crate) fn tuple_alignment(index: &NodeIndex, generic_args: &GenericArgs<'_>) -> u32 {
+    tuple_item_types_or_panic(generic_args)
+        .iter()
+        .map(|type_| type_::alignment(type_, index))
+        .max()
+        .expect("internal error: tuples should have at least two items")
+}
+
+// Tuples with the same item types share the same WGSL struct.
+pub(crate) fn tuple_transpiled_name(index: &NodeIndex, generic_args: &GenericArgs<'_>) -> String {
+    let mut name = format!("Tuple{}", generic_args.len());
+    for type_ in tuple_item_types_or_panic(generic_args) {
+        name += "_";
+        name += &type_.transpiled_type_name(index);
+    }
+    name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "_")
+}
+
+/// Returns the WGSL struct definition of a tuple type.
+pub(crate) fn transpile_tuple_struct(type_: &NodeSource<'_>, index: &NodeIndex) -> String {
+    let name = tuple_transpiled_name(index, &type_.generic_args);
+    let fields = tuple_item_types_or_panic(&type_.generic_args)
+        .iter()
+        .enumerate()
+        .map(|(position, type_)| {
+            let type_ = type_.transpiled_type_name(index);
+            format!("_{position}: {type_},")
+        })
+        .join("\n");
+    format!("struct {name} {{\n{fields}\n}}\n")
+}
+
+fn tuple_item_types_or_panic<'a>(generic_args: &GenericArgs<'a>) -> Vec<NodeSource<'a>> {
+    generic_args
+        .iter()
+        .map(|type_| {
+            type_
+                .clone()
+                .expect("internal error: invalid tuple item type")
+        })
+        .collect()
+}
+
+/// Returns the length of a fixed-size array type, if known.
+pub(crate) fn array_length(type_: &NodeSource<'_>) -> Option<u32> {
+    type_
+        .type_item()
+        .is_some_and(|item| (item as &dyn Any).is::<ArrayType>())
+        .then(|| generic_length(&type_.generic_args))?
+}
+
+fn generic_length(generic_args: &GenericArgs<'_>) -> Option<u32> {
+    if let NodeRef::Value(GenericValue::U32(length)) = generic_args.get(1)?.as_ref()?.node {
+        Some(length)
+    } else {
+        None
+    }
+}
+
+fn generic_args<'a>(
+    generics: &'a Repeated<TypeGenericArgs, 0, 1>,
+    index: &'a NodeIndex,
+) -> GenericArgs<'a> {
+    generics
+        .iter()
+        .flat_map(|generics| generics.args())
+        .map(|arg| arg.value(index))
+        .collect()
+}
+
+fn constant_generic_arg<'a>(
+    expr: &'a dyn Node,
+    var: Option<&'a VarIdentExpr>,
+    index: &'a NodeIndex,
+) -> Option<NodeSource<'a>> {
+    if let Some(param) = var.and_then(|var| generic_value_param(var, index)) {
+        return Some(NodeSource {
+            node: NodeRef::Type(param),
+            generic_args: vec![],
+        });
+    }
+    if expr.invalid_constant(index).is_some() {
+        return None;
+    }
+    let node = if let Some(value) = expr.evaluate_constant(&mut ConstantContext::new(index)) {
+        NodeRef::Value(GenericValue::from_constant(&value.data)?)
+    } else {
+        NodeRef::DeferredValue(expr)
+    };
+    Some(NodeSource {
+        node,
+        generic_args: vec![],
+    })
+}
+
+fn generic_value_param<'a>(
+    var: &'a VarIdentExpr,
+    index: &'a NodeIndex,
+) -> Option<&'a GenericParam> {
+    let source = var.source(index)?;
+    (source.node() as &dyn Any).downcast_ref::<GenericParam>()
+}
+
+fn check_generic_args(
+    node: &dyn Node,
+    item: &dyn TypeItem,
+    generics: &Repeated<TypeGenericArgs, 0, 1>,
+    ctx: &mut ValidationContext<'_>,
+) {
+    let error_count = ctx.errors.len();
+    check_generic_arg_list(node, item, generics.iter().next().map(|args| &**args), ctx);
+    if ctx.errors.len() == error_count {
+        check_specialized_array_lengths(item, generics, ctx);
+        enum_::check_specialized_payloads(node, item, &generic_args(generics, ctx.index), ctx);
+    }
+}
+
+/// Checks that generic arguments match the generic parameters of an item.
+pub(crate) fn check_generic_arg_list(
+    node: &dyn Node,
+    item: &dyn Node,
+    generics: Option<&TypeGenericArgs>,
+    ctx: &mut ValidationContext<'_>,
+) {
+    let generic_params = item.generic_params();
+    let expected_param_count = generic_params.len();
+    let actual_param_count = generics.map_or(0, |args| 1 + args.other_args.iter().len());
+    if expected_param_count != actual_param_count {
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "invalid number of generic parameters",
+            Some(&format!("{actual_param_count} parameter(s) specified here")),
+            &[(
+                item,
+                &format!("{expected_param_count} parameter(s) expected"),
+            )],
+        ));
+        return;
+    }
+    let args = generics.into_iter().flat_map(TypeGenericArgs::args);
+    for (param, arg) in generic_params.into_iter().zip(args) {
+        let is_value_arg = match arg {
+            GenericArg::Array(_) | GenericArg::Tuple(_) => Some(false),
+            GenericArg::Named(arg) => arg
+                .source(ctx.index)
+                .map(|_| arg.type_item(ctx.index).is_none()),
+            GenericArg::Value(_) => Some(true),
+        };
+        match (param.is_value(), is_value_arg) {
+            (true, Some(false)) | (false, Some(true)) => {
+                let expected = if param.is_value() {
+                    "expected a constant value"
+                } else {
+                    "expected a type"
+                };
+                ctx.errors.push(ValidationError::error(
+                    ctx,
+                    arg,
+                    "invalid generic argument",
+                    Some(expected),
+                    &[(param, "generic parameter defined here")],
+                ));
+            }
+            (true, Some(true)) => {
+                if let Some(param_type) = param.type_(ctx.index) {
+                    validations::check_invalid_const_expr_type(&param_type, arg, ctx);
+                }
+                if let GenericArg::Value(expr) = arg {
+                    validations::check_invalid_const_scope(&**expr, node, ctx);
+                    validations::check_generic_value_evaluation(&**expr, ctx);
+                }
+            }
+            (false, Some(false)) => {
+                if let Some(arg_type) = arg.value(ctx.index) {
+                    validations::check_generic_constraint(arg, param, &arg_type, ctx);
+                }
+            }
+            (_, None) => {}
+        }
+    }
+}
+
+fn check_specialized_array_lengths(
+    item: &dyn TypeItem,
+    generics: &Repeated<TypeGenericArgs, 0, 1>,
+    ctx: &mut ValidationContext<'_>,
+) {
+    let Some(args) = generics.iter().next() else {
+        return;
+    };
+    let generic_args = generic_args(generics, ctx.index);
+    if generic_args
+        .iter()
+        .any(|arg| arg.as_ref().is_none_or(NodeSource::is_generic))
+    {
+        return;
+    }
+    let invalid_array = item.fields().into_iter().find_map(|field| {
+        let field_type = field.type_(ctx.index)?;
+        invalid_specialized_array(&field_type, &generic_args, &mut vec![item.id], ctx.index)
+    });
+    if let Some((array, length)) = invalid_array {
+        let mut value_args = args.args().filter(|arg| {
+            arg.value(ctx.index)
+                .is_some_and(|arg| matches!(arg.node, NodeRef::Value(_)))
+        });
+        let node: &dyn Node = match (value_args.next(), value_args.next()) {
+            (Some(arg), None) => arg,
+            _ => &**args,
+        };
+        let label = if length.is_some() {
+            "array length must be greater than zero"
+        } else {
+            "array length cannot be calculated at compile time"
+        };
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "invalid array length",
+            Some(label),
+            &[(array, "array type with specialized length defined here")],
+        ));
+    }
+}
+
+// Generic arguments substituted to a type parameter are not checked, as they are validated
+// where they are defined.
+fn invalid_specialized_array<'a>(
+    type_: &NodeSource<'a>,
+    generic_args: &GenericArgs<'a>,
+    parent_ids: &mut Vec<u32>,
+    index: &'a NodeIndex,
+) -> Option<(&'a dyn TypeItem, Option<u32>)> {
+    let item = type_.type_item()?;
+    if !type_.is_generic() || (item as &dyn Any).is::<GenericParam>() {
+        return None;
+    }
+    let specialized = type_.specialized(generic_args, index);
+    let is_generic_length = type_
+        .generic_args
+        .get(1)
+        .and_then(Option::as_ref)
+        .is_some_and(NodeSource::is_generic);
+    if (item as &dyn Any).is::<ArrayType>() && is_generic_length {
+        let length = generic_length(&specialized.generic_args);
+        if length.is_none_or(|length| length == 0) {
+            return Some((item, length));
+        }
+    }
+    if let Some(invalid_array) = type_
+        .generic_args
+        .iter()
+        .flatten()
+        .find_map(|arg| invalid_specialized_array(arg, generic_args, parent_ids, index))
+    {
+        return Some(invalid_array);
+    }
+    if parent_ids.contains(&item.id) {
+        return None;
+    }
+    parent_ids.push(item.id);
+    let invalid_array = item.fields().into_iter().find_map(|field| {
+        let field_type = field.type_(index)?;
+        invalid_specialized_array(&field_type, &specialized.generic_args, parent_ids, index)
+    });
+    parent_ids.pop();
+    invalid_array
+}
+
+fn item_type<'a, 'b>(generic_args: &'b GenericArgs<'a>) -> &'b NodeSource<'a> {
+    generic_args[0]
+        .as_ref()
+        .expect("internal error: invalid array item type")
+}
+
+fn item_stride(generic_args: &GenericArgs<'_>, index: &NodeIndex) -> u32 {
+    let item_type = item_type(generic_args);
+    type_::round_up(
+        type_::alignment(item_type, index),
+        type_::size(item_type, index),
+    )
+}
+
+pub(crate) fn is_array(type_: &NodeSource<'_>) -> bool {
+    type_.type_item().is_some_and(|item| {
+        let item = item as &dyn Any;
+        item.is::<ArrayType>() || item.is::<RuntimeArrayType>()
+    })
+}
+
+pub(crate) fn is_runtime_array(type_: &NodeSource<'_>) -> bool {
+    type_
+        .type_item()
+        .is_some_and(|item| (item as &dyn Any).is::<RuntimeArrayType>())
+}
+
+pub(crate) fn array_item_type<'a>(type_: &NodeSource<'a>) -> Option<NodeSource<'a>> {
+    if is_array(type_) {
+        type_.generic_args[0].clone()
+    } else {
+        None
+    }
+}
+
+/// Returns the column count and the column type of a prelude matrix type.
+pub(crate) fn matrix_columns<'a>(
+    type_: &NodeSource<'a>,
+    index: &'a NodeIndex,
+) -> Option<(u32, NodeSource<'a>)> {
+    let item = prelude_native_struct(type_)?;
+    let (column_count, row_count) = item.ident.slice.strip_prefix("f32x")?.split_once('x')?;
+    Some((
+        column_count.parse().ok()?,
+        prelude_type(item, &format!("f32x{row_count}"), index)?,
+    ))
+}
+
+/// Returns the component count and the component type of a prelude vector type.
+pub(crate) fn vector_components<'a>(
+    type_: &NodeSource<'a>,
+    index: &'a NodeIndex,
+) -> Option<(u32, NodeSource<'a>)> {
+    let item = prelude_native_struct(type_)?;
+    let (component_type, component_count) = item.ident.slice.split_once('x')?;
+    Some((
+        component_count.parse().ok()?,
+        prelude_type(item, component_type, index)?,
+    ))
+}
+
+/// Returns the vector type resulting from a swizzle like `xy` or `bgr` on a prelude vector type.
+pub(crate) fn swizzle_type<'a>(
+    type_: &NodeSource<'a>,
+    swizzle: &str,
+    index: &'a NodeIndex,
+) -> Option<NodeSource<'a>> {
+    let item = prelude_native_struct(type_)?;
+    let (component_type, component_count) = item.ident.slice.split_once('x')?;
+    let component_count = component_count.parse::<usize>().ok()?;
+    let is_valid = (2..=4).contains(&swizzle.len())
+        && SWIZZLE_COMPONENTS.iter().any(|components| {
+            swizzle
+                .chars()
+                .all(|c| components[..component_count].contains(c))
+        });
+    is_valid.then(|| prelude_type(item, &format!("{component_type}x{}", swizzle.len()), index))?
+}
+
+/// Returns whether a type is a prelude atomic type.
+pub(crate) fn is_atomic(type_: &NodeSource<'_>) -> bool {
+    prelude_native_struct(type_).is_some_and(|item| item.ident.slice == "atomic")
+}
+
+/// Returns whether a type is or contains a prelude atomic type.
+pub(crate) fn contains_atomic(type_: &NodeSource<'_>, index: &NodeIndex) -> bool {
+    let Some(item) = type_.type_item() else {
+        return false;
+    };
+    if is_atomic(type_) {
+        true
+    } else if let Some(item_type) = array_item_type(type_) {
+        contains_atomic(&item_type, index)
+    } else if let Some(item_types) = tuple_item_types(type_) {
+        item_types
+            .iter()
+            .flatten()
+            .any(|item_type| contains_atomic(item_type, index))
+    } else if let Some(struct_) = (item as &dyn Any).downcast_ref::<StructItem>() {
+        !is_item_recursive(struct_, index)
+            && struct_.fields().iter().any(|field| {
+                field.type_(index).is_some_and(|field_type| {
+                    contains_atomic(&field_type.specialized(&type_.generic_args, index), index)
+                })
+            })
+    } else {
+        false
+    }
+}
+
+fn prelude_native_struct<'a>(type_: &NodeSource<'a>) -> Option<&'a NativeStructItem> {
+    let item = (type_.type_item()? as &dyn Any).downcast_ref::<NativeStructItem>()?;
+    (item.path == Path::new(PRELUDE_PATH)).then_some(item)
+}
+
+fn prelude_type<'a>(
+    prelude_item: &'a NativeStructItem,
+    name: &str,
+    index: &'a NodeIndex,
+) -> Option<NodeSource<'a>> {
+    let type_ = index.search_in_path(
+        Path::new(PRELUDE_PATH),
+        prelude_item,
+        &format!("`{name}` type"),
+        sources::type_criteria(),
+    )?;
+    Some(NodeSource {
+        node: NodeRef::Type(type_::to_item(type_)),
+        generic_args: vec![],
+    })
+}
@@ -1,7 +1,11 @@
+use crate::compilation::constant::{ConstantContext, ConstantValue, MAX_CONSTANT_LOOP_ITERATIONS};
 use crate::compilation::node::{Node, NodeSource};
 use crate::compilation::validation::ValidationContext;
 use crate::language::items;
-use crate::language::patterns::{Ident, StringLiteral};
+use crate::language::items::block::Block;
+use crate::language::items::type_::GenericParam;
+use crate::language::patterns::{I32Literal, Ident, StringLiteral, U32Literal};
+use crate::language::type_ref;
 use crate::ValidationError;
 use regex::Regex;
 use std::sync::OnceLock;
@@ -38,6 +42,106 @@ pub(crate) fn check_duplicated_items(item: &impl Node, ctx: &mut ValidationConte
     }
 }
 
+pub(crate) fn check_loop_control_stmts(block: &Block, ctx: &mut ValidationContext<'_>) {
+    for stmt in block.loop_control_stmts() {
+        let keyword = if stmt.as_break().is_some() {
+            "break"
+        } else {
+            "continue"
+        };
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            stmt,
+            &format!("`{keyword}` statement used outside a loop"),
+            Some("not allowed statement"),
+            &[],
+        ));
+    }
+}
+
+pub(crate) fn check_constant_evaluation(node: &impl Node, ctx: &mut ValidationContext<'_>) {
+    let mut constant_ctx = ConstantContext::new(ctx.index);
+    node.evaluate_constant(&mut constant_ctx);
+    check_interrupted_evaluation(node, &constant_ctx, ctx);
+}
+
+// Values depending on generic parameters are only known once specialized, so they are not checked.
+pub(crate) fn check_generic_value_evaluation(
+    node: &dyn Node,
+    ctx: &mut ValidationContext<'_>,
+) -> Option<ConstantValue> {
+    if node.type_(ctx.index).is_none() || node.invalid_constant(ctx.index).is_some() {
+        return None;
+    }
+    let mut constant_ctx = ConstantContext::new(ctx.index);
+    let value = node.evaluate_constant(&mut constant_ctx);
+    let is_interrupted = check_interrupted_evaluation(node, &constant_ctx, ctx);
+    if value.is_none() && !is_interrupted && !constant_ctx.is_generic_value_missing() {
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "invalid `const` evaluation",
+            Some("value cannot be calculated at compile time"),
+            &[],
+        ));
+    }
+    value
+}
+
+// Returns whether an error has been reported.
+fn check_interrupted_evaluation(
+    node: &dyn Node,
+    constant_ctx: &ConstantContext<'_>,
+    ctx: &mut ValidationContext<'_>,
+) -> bool {
+    if constant_ctx.is_loop_iteration_limit_reached() {
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "too long `const` evaluation",
+            Some(&format!(
+                "evaluation has exceeded the limit of {MAX_CONSTANT_LOOP_ITERATIONS} loop iterations"
+            )),
+            &[],
+        ));
+    } else if constant_ctx.is_index_out_of_bounds() {
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "index out of bounds in `const` evaluation",
+            Some("evaluation has accessed an array item with an out of bounds index"),
+            &[],
+        ));
+    } else if constant_ctx.is_non_finite_value() {
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "non-finite value in `const` evaluation",
+            Some("evaluation has produced a NaN or infinite `f32` value"),
+            &[],
+        ));
+    } else if constant_ctx.is_division_by_zero() {
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "division by zero in `const` evaluation",
+            Some("evaluation has divided an integer by zero"),
+            &[],
+        ));
+    } else if constant_ctx.is_native_fn_implementation_missing() {
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "invalid `const` evaluation",
+            Some("evaluation has called a `const` native function without constant implementation for its argument types"),
+            &[],
+        ));
+    } else {
+        return false;
+    }
+    true
+}
+
 pub(crate) fn check_recursive_items(item: &impl Node, ctx: &mut ValidationContext<'_>) {
     if items::is_item_recursive(item, ctx.index) {
         ctx.errors.push(ValidationError::error(
@@ -56,15 +160,25 @@ pub(crate) fn check_invalid_expr_type(
     check_no_return: bool,
     ctx: &mut ValidationContext<'_>,
 ) {
-    if let (Some(expected_type), Some(actual_type)) =
-        (expected.type_(ctx.index), actual.type_(ctx.index))
-    {
+    if let Some(expected_type) = expected.type_(ctx.index) {
+        check_invalid_expr_specialized_type(expected, &expected_type, actual, check_no_return, ctx);
+    }
+}
+
+pub(crate) fn check_invalid_expr_specialized_type(
+    expected: &dyn Node,
+    expected_type: &NodeSource<'_>,
+    actual: &dyn Node,
+    check_no_return: bool,
+    ctx: &mut ValidationContext<'_>,
+) {
+    if let Some(actual_type) = actual.type_(ctx.index) {
         if (actual_type.is_no_return() || expected_type.is_no_return()) && !check_no_return {
             return;
         }
-        if actual_type.are_same_types(&expected_type) == Some(false) {
-            let expected_type_name = expected_type.name_or_no_return();
-            let actual_type_name = actual_type.name_or_no_return();
+        if actual_type.are_same_types(expected_type, ctx.index) == Some(false) {
+            let expected_type_name = expected_type.name_or_no_return(ctx.index);
+            let actual_type_name = actual_type.name_or_no_return(ctx.index);
             ctx.errors.push(ValidationError::error(
                 ctx,
                 actual,
@@ -85,9 +199,9 @@ pub(crate) fn check_invalid_const_expr_type(
     ctx: &mut ValidationContext<'_>,
 ) {
     if let Some(actual_type) = actual.type_(ctx.index) {
-        if actual_type.are_same_types(expected_type) == Some(false) {
-            let expected_type_name = expected_type.name_or_no_return();
-            let actual_type_name = actual_type.name_or_no_return();
+        if actual_type.are_same_types(expected_type, ctx.index) == Some(false) {
+            let expected_type_name = expected_type.name_or_no_return(ctx.index);
+            let actual_type_name = actual_type.name_or_no_return(ctx.index);
             ctx.errors.push(ValidationError::error(
                 ctx,
                 actual,
@@ -101,6 +215,33 @@ pub(crate) fn check_invalid_const_expr_type(
     }
 }
 
+// Returns whether the expression type is `i32`, `u32` or unknown.
+pub(crate) fn check_integer_expr_type(expr: &impl Node, ctx: &mut ValidationContext<'_>) -> bool {
+    if let Some(type_) = expr.type_(ctx.index) {
+        let integer_types = [
+            I32Literal::i32_type(expr, ctx.index),
+            U32Literal::u32_type(expr, ctx.index),
+        ];
+        if integer_types
+            .into_iter()
+            .all(|integer_type| type_.type_item().is_none_or(|t| t.id != integer_type.id))
+        {
+            let type_name = type_.name_or_no_return(ctx.index);
+            ctx.errors.push(ValidationError::error(
+                ctx,
+                expr,
+                "invalid expression type",
+                Some(&format!(
+                    "expression type is `{type_name}` but expected type is `i32` or `u32`"
+                )),
+                &[],
+            ));
+            return false;
+        }
+    }
+    true
+}
+
 pub(crate) fn check_invalid_const_scope(
     checked: &impl Node,
     const_declaration: &dyn Node,
@@ -135,6 +276,55 @@ pub(crate) fn check_arg_name(
     }
 }
 
+pub(crate) fn check_generic_constraint(
+    node: &dyn Node,
+    param: &GenericParam,
+    arg: &NodeSource<'_>,
+    ctx: &mut ValidationContext<'_>,
+) {
+    let Some(interface) = param.interface(ctx.index) else {
+        return;
+    };
+    if !interface.is_implementable_by(arg) {
+        let type_name = arg.name_or_no_return(ctx.index);
+        let interface_name = &interface.ident.slice;
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "unsatisfied generic constraint",
+            Some(&format!(
+                "`{type_name}` does not implement `{interface_name}` interface"
+            )),
+            &[
+                (param, "constraint defined here"),
+                (
+                    &*interface.ident,
+                    "`native` interface only implemented by native types of its module",
+                ),
+            ],
+        ));
+    } else if let Some(missing_fn) = interface.missing_fn(arg, ctx.index) {
+        let type_name = arg.name_or_no_return(ctx.index);
+        let interface_name = &interface.ident.slice;
+        let fn_name = &missing_fn.signature.ident.slice;
+        ctx.errors.push(ValidationError::error(
+            ctx,
+            node,
+            "unsatisfied generic constraint",
+            Some(&format!(
+                "`{type_name}` does not implement `{interface_name}` interface"
+            )),
+            &[
+                (param, "constraint defined here"),
+                (
+                    &*missing_fn.signature,
+                    &format!("`{fn_name}` function not implemented for `{type_name}`"),
+                ),
+            ],
+        ));
+    }
+}
+
 pub(crate) fn check_no_return_type(expr: &impl Node, ctx: &mut ValidationContext<'_>) {
     if expr.type_(ctx.index).is_some_and(|t| t.is_no_return()) {
         ctx.errors.push(ValidationError::error(
@@ -147,6 +337,26 @@ pub(crate) fn check_no_return_type(expr: &impl Node, ctx: &mut ValidationContext
     }
 }
 
+pub(crate) fn check_copyable_expr(expr: &dyn Node, ctx: &mut ValidationContext<'_>) {
+    let Some(type_) = expr.type_(ctx.index) else {
+        return;
+    };
+    let reason = if type_ref::is_runtime_array(&type_) {
+        "runtime-sized arrays cannot be copied"
+    } else if type_ref::contains_atomic(&type_, ctx.index) {
+        "atomic values cannot be copied"
+    } else {
+        return;
+    };
+    ctx.errors.push(ValidationError::error(
+        ctx,
+        expr,
+        "invalid expression type",
+        Some(reason),
+        &[],
+    ));
+}
+
 // Checks that the string literal contains only `${param}` placeholders for known parameters.
 pub(crate) fn check_native_code<'a>(
     string_literal: &StringLiteral,
@@ -20,8 +20,8 @@ fn run_valid_code(
                 "{}={}",
                 name,
                 match props.type_name.as_str() {
-                    "i32" => format!("{}", to_i32(&runner.read(name))),
-                    "u32" | "bool" => format!("{}", to_u32(&runner.read(name))),
+                    "i32" | "atomic<i32>" => format!("{}", to_i32(&runner.read(name))),
+                    "u32" | "atomic<u32>" | "bool" => format!("{}", to_u32(&runner.read(name))),
                     "f32" => format!("{}", to_f32(&runner.read(name))),
                     _ => format!("{:?}", runner.read(name)),
                 }
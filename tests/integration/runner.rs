@@ -66,3 +66,147 @@ fn execute_run_shaders_at_each_frame() {
     runner.run_step();
     assert_eq!(runner.read(buffer_name), &[42, 0, 0, 0]);
 }
+
+#[test]
+fn execute_run_shaders_at_specified_frequency() {
+    let buffer_name = "conditions.periodic_count";
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    let mut runner = Runner::new(program, None, Some((4, 3)));
+    runner.run_step();
+    assert_eq!(runner.read(buffer_name), &[1, 0, 0, 0]);
+    runner.run_step();
+    runner.run_step();
+    assert_eq!(runner.read(buffer_name), &[1, 0, 0, 0]);
+    runner.run_step();
+    assert_eq!(runner.read(buffer_name), &[2, 0, 0, 0]);
+}
+
+#[test]
+fn execute_run_shaders_only_if_condition_is_true() {
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    let mut runner = Runner::new(program, None, Some((4, 3)));
+    runner.run_step();
+    assert_eq!(runner.read("conditions.frame_count"), &[1, 0, 0, 0]);
+    assert_eq!(runner.read("conditions.paused_frame_count"), &[0, 0, 0, 0]);
+    runner.write("conditions.is_paused", &[1, 0, 0, 0]);
+    runner.run_step();
+    assert_eq!(runner.read("conditions.frame_count"), &[1, 0, 0, 0]);
+    assert_eq!(runner.read("conditions.paused_frame_count"), &[1, 0, 0, 0]);
+    runner.resize("conditions.items", 2);
+    runner.run_step();
+    assert_eq!(runner.read("conditions.items"), &[1, 0, 0, 0, 0, 0, 0, 0]);
+    runner.write("conditions.is_paused", &[0, 0, 0, 0]);
+    runner.run_step();
+    assert_eq!(runner.read("conditions.items"), &[2, 0, 0, 0, 1, 0, 0, 0]);
+}
+
+#[test]
+fn update_frame_seed_at_each_frame() {
+    let buffer_name = "prelude.frame_seed";
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    let mut runner = Runner::new(program, None, Some((4, 3)));
+    runner.run_step();
+    assert_eq!(runner.read(buffer_name), &[0, 0, 0, 0]);
+    runner.run_step();
+    assert_eq!(runner.read(buffer_name), &[1, 0, 0, 0]);
+}
+
+#[test]
+fn start_runtime_sized_buffer_with_one_item() {
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    assert_eq!(program.buffers["runtime_arrays.values"].size_bytes, 4);
+    let runner = Runner::new(program, None, Some((4, 3)));
+    assert_eq!(runner.read("runtime_arrays.values"), &[0, 0, 0, 0]);
+}
+
+#[test]
+fn resize_runtime_sized_buffer() {
+    let buffer_name = "runtime_arrays.values";
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    let mut runner = Runner::new(program, None, Some((4, 3)));
+    runner.run_step();
+    assert_eq!(runner.read(buffer_name), &[10, 0, 0, 0]);
+    runner.resize(buffer_name, 3);
+    assert_eq!(
+        runner.read(buffer_name),
+        &[10, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+    );
+    runner.write(buffer_name, &[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0]);
+    assert_eq!(
+        runner.read(buffer_name),
+        &[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0]
+    );
+    runner.run_step();
+    assert_eq!(
+        runner.read(buffer_name),
+        &[10, 0, 0, 0, 11, 0, 0, 0, 12, 0, 0, 0]
+    );
+    assert_eq!(runner.read("runtime_arrays.value_count"), &[3, 0, 0, 0]);
+}
+
+#[test]
+fn dispatch_invocations_for_each_buffer_item() {
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    let mut runner = Runner::new(program, None, Some((4, 3)));
+    runner.resize("dispatch.items", 200);
+    runner.run_step();
+    let items = runner.read("dispatch.items");
+    assert_eq!(items.len(), 800);
+    assert_eq!(&items[..4], &[1, 0, 0, 0]);
+    assert_eq!(&items[796..], &[200, 0, 0, 0]);
+    assert_eq!(runner.read("dispatch.item_sum"), 20_100_u32.to_le_bytes());
+}
+
+#[test]
+fn dispatch_invocations_from_buffer_value() {
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    let mut runner = Runner::new(program, None, Some((4, 3)));
+    runner.run_step();
+    runner.write("indirect.live_count", &5_u32.to_le_bytes());
+    runner.write("indirect.empty_count", &1_u32.to_le_bytes());
+    runner.run_step();
+    assert_eq!(
+        runner.read("indirect.particles"),
+        &[1, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0, 4, 0, 0, 0, 5, 0, 0, 0]
+    );
+    assert_eq!(
+        runner.read("indirect.empty_particles"),
+        &[1, 0, 0, 0, 0, 0, 0, 0]
+    );
+}
+
+#[test]
+fn dispatch_workgroups_of_prelude_size() {
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    let workgroup_sizes: Vec<_> = program
+        .run_shaders
+        .iter()
+        .map(|shader| shader.workgroup_size)
+        .filter(|&size| size > 1)
+        .collect();
+    let mut runner = Runner::new(program, None, Some((4, 3)));
+    runner.run_step();
+    let prelude_size = runner.read("shared.workgroup_size");
+    assert!(!workgroup_sizes.is_empty());
+    for size in workgroup_sizes {
+        assert_eq!(prelude_size, size.to_le_bytes());
+    }
+}
+
+#[test]
+fn resize_fixed_size_buffer() {
+    let buffer_name = "runtime_arrays.value_count";
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    let mut runner = Runner::new(program, None, Some((4, 3)));
+    runner.resize(buffer_name, 3);
+    runner.resize("non_existing", 3);
+    assert_eq!(runner.read(buffer_name), &[0, 0, 0, 0]);
+}
+
+#[test]
+#[should_panic = "runtime-sized array should have at least one item"]
+fn resize_buffer_with_no_item() {
+    let program = shad::compile(Path::new("./cases_valid/items")).unwrap();
+    let mut runner = Runner::new(program, None, Some((4, 3)));
+    runner.resize("runtime_arrays.values", 0);
+}